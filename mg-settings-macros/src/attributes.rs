@@ -21,7 +21,7 @@
 
 use quote::Tokens;
 use syn;
-use syn::{Attribute, Data, DataEnum, DataStruct, Field, Ident, Variant};
+use syn::{Attribute, Data, DataEnum, DataStruct, Field, Ident, Type, Variant};
 use syn::{MetaList, MetaNameValue};
 use syn::Data::{Enum, Struct};
 use syn::Lit::Str;
@@ -30,7 +30,7 @@ use syn::NestedMeta::Meta;
 use syn::Type::Path;
 use syn::Fields;
 
-use self::VariantInfo::{CommandInfo, SpecialCommandInfo};
+use self::VariantInfo::{CommandInfo, NestedSettingsInfo, SpecialCommandInfo};
 use string::to_dash_name;
 
 fn collect_attrs(name: &str, attrs: &[Attribute], hidden: &mut bool, description: &mut String, is_count: &mut bool)
@@ -119,6 +119,12 @@ fn collect_and_transform_variant(variant: &Variant) -> VariantInfo {
 }
 
 fn collect_and_transform_field(field: &Field) -> VariantInfo {
+    if is_nested(&field.attrs) {
+        return NestedSettingsInfo(NestedSettings {
+            name: field.ident.as_ref().unwrap().to_string(),
+            typ: field.ty.clone(),
+        });
+    }
     let mut command = Command::new();
     command.name = field.ident.as_ref().unwrap().to_string();
     if let Some(special_command) = collect_attrs(&command.name, &field.attrs, &mut command.hidden,
@@ -161,13 +167,38 @@ pub struct SpecialCommand {
     pub name: String,
 }
 
+#[derive(Debug)]
+pub struct NestedSettings {
+    pub name: String,
+    pub typ: Type,
+}
+
 /// Struct holding metadata information about all the variants.
 #[derive(Debug)]
 pub enum VariantInfo {
     CommandInfo(Command),
+    NestedSettingsInfo(NestedSettings),
     SpecialCommandInfo(SpecialCommand),
 }
 
+/// Check if the attributes contain `#[settings(nested)]`.
+pub fn is_nested(attrs: &[Attribute]) -> bool {
+    attrs.iter()
+        .any(|attribute|
+            if let Some(List(MetaList { ref ident, ref nested, .. })) = attribute.interpret_meta() {
+                ident == "settings" && nested.iter().any(|argument|
+                    if let Meta(Word(ref argument)) = *argument {
+                        argument == "nested"
+                    }
+                    else {
+                        false
+                    })
+            }
+            else {
+                false
+            })
+}
+
 /// Create the EnumMetaData impl.
 pub fn to_metadata_impl(name: &Ident, body: &Data) -> (Tokens, Vec<VariantInfo>) {
     let variant_infos = transform_enum(body);
@@ -189,10 +220,23 @@ pub fn to_metadata_impl(name: &Ident, body: &Data) -> (Tokens, Vec<VariantInfo>)
             else {
                 None
             });
+        let nested_metadata = variant_infos.iter()
+            .filter_map(|info| if let NestedSettingsInfo(ref nested) = *info {
+                let prefix = format!("{}.", nested.name.replace('_', "-"));
+                let typ = &nested.typ;
+                Some(quote! {
+                    vec.extend(<#typ as ::mg_settings::EnumMetaData>::get_metadata().into_iter()
+                        .map(|(name, metadata)| (format!("{}{}", #prefix, name), metadata)));
+                })
+            }
+            else {
+                None
+            });
         quote! {
             impl ::mg_settings::EnumMetaData for #name {
                 fn get_metadata() -> ::std::collections::HashMap<String, ::mg_settings::MetaData> {
                     let mut vec = vec![#(#metadata),*];
+                    #(#nested_metadata)*
                     let iter = vec.drain(..);
                     iter.collect()
                 }
@@ -31,6 +31,7 @@ pub fn expand_commands_enum(mut ast: DeriveInput) -> Tokens {
     let name = &ast.ident;
     let (metadata_impl, variant_infos) = to_metadata_impl(name, &mut ast.data);
     let special_command_impl = to_special_command_impl(name, &variant_infos);
+    let to_str_impl = to_enum_to_str_impl(name, &variant_infos);
     let mut variant_values = vec![];
     let mut variant_names_with_argument = vec![];
     let mut variant_names_without_argument = vec![];
@@ -95,10 +96,16 @@ pub fn expand_commands_enum(mut ast: DeriveInput) -> Tokens {
             }
 
             #fn_has_argument
+
+            fn names() -> Vec<String> {
+                vec![#(#variant_names.to_string()),*]
+            }
         }
 
         #clone
 
+        #to_str_impl
+
         #metadata_impl
         #special_command_impl
     }
@@ -158,6 +165,68 @@ fn derive_clone(ast: &DeriveInput) -> Tokens {
     }
 }
 
+/// Create the implementation of `EnumToStr`, giving back the name, argument and count of the commands.
+fn to_enum_to_str_impl(name: &Ident, variant_infos: &[VariantInfo]) -> Tokens {
+    let mut name_arms = vec![];
+    let mut argument_arms = vec![];
+    let mut count_arms = vec![];
+    for info in variant_infos {
+        let (command_name, pattern, argument, count) =
+            match *info {
+                CommandInfo(ref command) => {
+                    let ident = Ident::from(command.name.as_ref());
+                    if !command.has_argument {
+                        (&command.name, quote! { #name::#ident }, quote! { None }, quote! { None })
+                    }
+                    else if command.is_count {
+                        (&command.name, quote! { #name::#ident(count) }, quote! { None }, quote! { count })
+                    }
+                    else if command.is_optional {
+                        (&command.name, quote! { #name::#ident(ref argument) }, quote! { argument.clone() },
+                         quote! { None })
+                    }
+                    else {
+                        (&command.name, quote! { #name::#ident(ref argument) }, quote! { Some(argument.clone()) },
+                         quote! { None })
+                    }
+                },
+                SpecialCommandInfo(ref command) => {
+                    let ident = Ident::from(command.name.as_ref());
+                    (&command.name, quote! { #name::#ident(ref argument) }, quote! { Some(argument.clone()) },
+                     quote! { None })
+                },
+                _ => continue,
+            };
+        let dash_name = to_dash_name(command_name);
+        name_arms.push(quote! { #pattern => #dash_name, });
+        argument_arms.push(quote! { #pattern => #argument, });
+        count_arms.push(quote! { #pattern => #count, });
+    }
+    quote! {
+        impl ::mg_settings::EnumToStr for #name {
+            #[allow(unused_variables)]
+            fn name(&self) -> &'static str {
+                match *self {
+                    #(#name_arms)*
+                }
+            }
+
+            fn argument(&self) -> Option<String> {
+                match *self {
+                    #(#argument_arms)*
+                }
+            }
+
+            #[allow(unused_variables)]
+            fn count(&self) -> Option<u32> {
+                match *self {
+                    #(#count_arms)*
+                }
+            }
+        }
+    }
+}
+
 fn to_special_command_impl(name: &Ident, variant_infos: &[VariantInfo]) -> Tokens {
     let mut identifiers = vec![];
     let mut incremental_identifiers = vec![];
@@ -74,8 +74,23 @@ pub fn setting(input: TokenStream) -> TokenStream {
     expanded.into()
 }
 
-#[proc_macro_derive(Settings, attributes(help))]
+#[proc_macro_derive(Settings, attributes(alias, default, help, non_empty, one_of, range, settings, validate))]
 /// Derive Settings.
+///
+/// A field can be given other names, which still set it:
+///
+/// ``` ignore
+/// #[alias = "zoom-level"]
+/// zoom: i64,
+/// #[settings(renamed_from = "zoom-level", note = "the level is now a percentage")]
+/// zoom: i64,
+/// ```
+///
+/// The names given by `renamed_from` are deprecated: the parser gives a warning with the note when
+/// they are used.
+/// NOTE: the `deprecated` attribute cannot be used for that, like
+/// `#[deprecated(renamed_from = "zoom-level")]`, since rustc only accepts its `since` and `note`
+/// arguments.
 pub fn settings(input: TokenStream) -> TokenStream {
     init_logger();
     let ast = syn::parse(input).unwrap();
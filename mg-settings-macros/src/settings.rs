@@ -20,15 +20,22 @@
  */
 
 use quote::Tokens;
-use syn::{Data, DataEnum, DataStruct, Ident, DeriveInput};
+use syn;
+use syn::{Data, DataEnum, DataStruct, Expr, Field, Ident, DeriveInput, Lit, MetaNameValue, NestedMeta, Path};
 use syn::Data::{Enum, Struct};
-use syn::Meta::{List, Word};
+use syn::Meta::{List, NameValue, Word};
 use syn::NestedMeta::Meta;
-use syn::{MetaList, Type, TypePath, Fields};
+use syn::{GenericArgument, MetaList, PathArguments, Type, TypePath, Fields};
 
-use attributes::to_metadata_impl;
+use attributes::{is_nested, to_metadata_impl};
 use string::{snake_to_camel, to_dash_name};
 
+/// The primitive floating-point types.
+const FLOAT_TYPES: &[&str] = &["f32", "f64"];
+
+/// The primitive integer types.
+const INTEGER_TYPES: &[&str] = &["i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize"];
+
 /// Expand the required trais for the derive Setting attribute.
 pub fn expand_setting_enum(ast: DeriveInput) -> Tokens {
     let name = ast.ident.clone();
@@ -64,10 +71,7 @@ pub fn expand_setting_enum(ast: DeriveInput) -> Tokens {
         fn from_str(string: &str) -> Result<Self, Self::Err> {
             match string {
                 #(#choice_names1 => Ok(#qualified_names),)*
-                _ => Err(::mg_settings::errors::SettingError::UnknownChoice {
-                    actual: string.to_string(),
-                    expected: vec![#(#choice_names2),*],
-                }),
+                _ => Err(::mg_settings::errors::SettingError::unknown_choice(string, vec![#(#choice_names2),*])),
             }
         }
     };
@@ -88,18 +92,41 @@ pub fn expand_setting_enum(ast: DeriveInput) -> Tokens {
         };
 
     let completion_values_impl = quote! {
-        impl CompletionValues for #name {
+        impl ::mg_settings::CompletionValues for #name {
             fn completion_values() -> Vec<String> {
                 vec![#(#choice_names1.to_string()),*]
             }
         }
     };
 
+    let qualified_names = variant_names.iter()
+        .map(|variant_name| quote! {
+            #name::#variant_name
+        });
+
+    let setting_value_impl = quote! {
+        impl ::mg_settings::SettingValue for #name {
+            fn to_value(&self) -> ::mg_settings::Value {
+                let choice =
+                    match *self {
+                        #(#qualified_names => #choice_names1,)*
+                    };
+                ::mg_settings::Value::Str(choice.to_string())
+            }
+
+            fn value_type() -> ::mg_settings::ValueType {
+                ::mg_settings::ValueType::Choice(vec![#(#choice_names2.to_string()),*])
+            }
+        }
+    };
+
     quote! {
         #default_impl
 
         #completion_values_impl
 
+        #setting_value_impl
+
         impl ::std::str::FromStr for #name {
             type Err = ::mg_settings::errors::SettingError;
 
@@ -115,10 +142,13 @@ pub fn expand_settings_enum(ast: DeriveInput) -> Tokens {
     let variant_name = Ident::from(format!("{}Variant", name));
     let variant_enum = to_enums(&variant_name, &ast.data);
     let settings_impl = to_settings_impl(name, &variant_name, &ast.data);
+    let default_impl = to_default_impl(name, &ast.data);
     let (metadata_impl, _) = to_metadata_impl(name, &ast.data);
     quote! {
         #variant_enum
 
+        #default_impl
+
         #settings_impl
 
         #metadata_impl
@@ -127,11 +157,63 @@ pub fn expand_settings_enum(ast: DeriveInput) -> Tokens {
     }
 }
 
-/// Check if a type is a custom type (including enum).
-fn is_custom_type(ident: &Ident) -> bool {
-    match ident.to_string().as_ref() {
-        "bool" | "f64" | "i64" | "String" => false,
-        _ => true,
+/// Create the impl Default from the `#[default = value]` attributes of the fields, or nothing if no field
+/// has this attribute.
+/// The value is a literal, or a string containing a Rust expression for the fields that are not of type
+/// `String`.
+fn to_default_impl(name: &Ident, settings_struct: &Data) -> Tokens {
+    if let Struct(DataStruct { fields: Fields::Named(ref fields), .. }) = *settings_struct {
+        let mut has_default = false;
+        let mut names = vec![];
+        let mut values = vec![];
+        for field in &fields.named {
+            let mut value = quote! { ::std::default::Default::default() };
+            for attribute in &field.attrs {
+                if let Some(NameValue(MetaNameValue { ref ident, ref lit, .. })) = attribute.interpret_meta() {
+                    if ident == "default" {
+                        has_default = true;
+                        value = to_default_value(&field.ty, lit);
+                    }
+                }
+            }
+            names.push(field.ident.clone());
+            values.push(value);
+        }
+
+        if has_default {
+            quote! {
+                impl ::std::default::Default for #name {
+                    fn default() -> Self {
+                        #name {
+                            #(#names: #values,)*
+                        }
+                    }
+                }
+            }
+        }
+        else {
+            quote! {
+            }
+        }
+    }
+    else {
+        panic!("Not a struct");
+    }
+}
+
+/// Create the expression of the default value `lit` of a field of type `typ`.
+fn to_default_value(typ: &Type, lit: &Lit) -> Tokens {
+    let ident = type_ident(typ).map(|ident| ident.to_string()).unwrap_or_default();
+    match *lit {
+        Lit::Str(ref string) if ident == "String" => quote! { #string.to_string() },
+        Lit::Str(ref string) if ident == "PathBuf" => quote! { ::mg_settings::settings::expand_tilde(#string) },
+        Lit::Str(ref string) => {
+            let expression: Expr = syn::parse_str(&string.value())
+                .unwrap_or_else(|_| panic!("Invalid default value `{}`, expecting an expression", string.value()));
+            quote! { #expression }
+        },
+        Lit::Int(ref int) if FLOAT_TYPES.contains(&ident.as_str()) => quote! { #int as #typ },
+        ref lit => quote! { #lit },
     }
 }
 
@@ -146,7 +228,13 @@ fn to_enums(variant_name: &Ident, settings_struct: &Data) -> Tokens {
                 field_names.push(ident);
                 let ident = Ident::from(snake_to_camel(&ident.to_string()));
                 names.push(ident);
-                types.push(field.ty.clone());
+                let typ = &field.ty;
+                if is_nested(&field.attrs) {
+                    types.push(quote! { <#typ as ::mg_settings::settings::Settings>::Variant });
+                }
+                else {
+                    types.push(quote! { #typ });
+                }
             }
         }
         let names1 = &names;
@@ -167,67 +255,134 @@ fn to_settings_impl(name: &Ident, variant_name: &Ident, settings_struct: &Data)
     if let &Struct(DataStruct { fields: Fields::Named(ref fields), .. }) = settings_struct {
         let mut names = vec![];
         let mut capitalized_names = vec![];
-        let mut original_types = vec![];
+        let mut conversions = vec![];
+        let mut updates = vec![];
+        let mut toggles = vec![];
+        let mut flag_names = vec![];
+        let mut flag_variants = vec![];
+        let mut non_flag_names = vec![];
+        let mut name_patterns = vec![];
+        let mut aliases = vec![];
+        let mut non_flag_types = vec![];
+        let mut setting_names = vec![];
+        let mut nested_fields = vec![];
         let mut types = vec![];
         for field in &fields.named {
             if let Some(ref ident) = field.ident {
+                if is_nested(&field.attrs) {
+                    let variant = Ident::from(snake_to_camel(&ident.to_string()));
+                    let typ = &field.ty;
+                    let prefix = format!("{}.", ident.to_string().replace('_', "-"));
+                    setting_names.push(quote! {
+                        names.extend(<#typ as ::mg_settings::settings::Settings>::setting_names().into_iter()
+                            .map(|name| format!("{}{}", #prefix, name)));
+                    });
+                    nested_fields.push(NestedField {
+                        ident: ident.clone(),
+                        name: ident.to_string().replace('_', "-"),
+                        typ: typ.clone(),
+                        variant: quote! { #variant_name::#variant },
+                    });
+                    continue;
+                }
+                types.push(&field.ty);
                 let ident_string = ident.to_string();
+                let setting_name = ident_string.replace('_', "-");
+                setting_names.push(quote! {
+                    names.push(#setting_name.to_string());
+                });
+                let field_aliases = to_aliases(field);
+                let mut all_names = vec![setting_name.clone()];
+                for &(ref alias, deprecated, ref note) in &field_aliases {
+                    all_names.push(alias.clone());
+                    let note =
+                        match *note {
+                            Some(ref note) => quote! { Some(#note.to_string()) },
+                            None => quote! { None },
+                        };
+                    aliases.push(quote! {
+                        ::mg_settings::settings::SettingAlias {
+                            alias: #alias.to_string(),
+                            deprecated: #deprecated,
+                            name: #setting_name.to_string(),
+                            note: #note,
+                        }
+                    });
+                }
+                let patterns = all_names.iter();
+                name_patterns.push(quote! { #(#patterns)|* });
                 let ident = Ident::from(ident_string.clone());
-                names.push(ident);
-                let ident = Ident::from(snake_to_camel(&ident_string));
-                capitalized_names.push(
-                    quote! {
-                        #variant_name::#ident
+                let capitalized_name = Ident::from(snake_to_camel(&ident_string));
+                let capitalized_name = quote! {
+                    #variant_name::#capitalized_name
+                };
+                let type_name = type_name(&field.ty);
+                let unsupported_operator = quote! {
+                    Err(::mg_settings::errors::SettingError::UnsupportedOperator {
+                        operator: operator.to_string(),
+                        typ: #type_name.to_string(),
+                    })
+                };
+                let typ = &field.ty;
+                let validation =
+                    match to_validation(field) {
+                        Some(validation) => quote! { result.and_then(#validation) },
+                        None => quote! { result },
+                    };
+                let update = to_update(typ).unwrap_or(unsupported_operator);
+                updates.push(quote! {{
+                    let current = &self.#ident;
+                    let result: ::std::result::Result<#typ, ::mg_settings::errors::SettingError> = #update;
+                    #validation
+                }});
+                if type_ident(&field.ty).map(|ident| ident == "bool").unwrap_or(false) {
+                    toggles.push(quote! {
+                        Ok(#capitalized_name(!self.#ident))
                     });
-
-                if let Type::Path(TypePath { ref path, .. }) = field.ty {
-                    original_types.push(&path.segments[0].ident);
-                    types.push(to_value_type(&path.segments[0].ident));
+                    let on_names = all_names.iter();
+                    let off_names = all_names.iter().map(|name| format!("no{}", name));
+                    flag_names.push(quote! { #(#on_names)|* });
+                    flag_variants.push(quote! { #capitalized_name(true) });
+                    flag_names.push(quote! { #(#off_names)|* });
+                    flag_variants.push(quote! { #capitalized_name(false) });
                 }
+                else {
+                    let patterns = all_names.iter();
+                    non_flag_names.push(quote! { #(#patterns)|* });
+                    non_flag_types.push(type_name.clone());
+                    toggles.push(quote! {
+                        Err(::mg_settings::errors::SettingError::WrongType {
+                            actual: #type_name.to_string(),
+                            expected: "bool".to_string(),
+                        })
+                    });
+                }
+                names.push(ident);
+                capitalized_names.push(capitalized_name);
+                let conversion = to_conversion(typ);
+                conversions.push(quote! {{
+                    let result: ::std::result::Result<#typ, ::mg_settings::errors::SettingError> = #conversion;
+                    #validation
+                }});
             }
         }
-        let string_names: Vec<_> = names.iter()
-            .map(|ident| ident.to_string().replace("_", "-"))
-            .collect();
-        let string_names = &string_names;
+        let string_names = &name_patterns;
         let capitalized_names = &capitalized_names;
         let names1 = &names;
         let names2 = &names;
         let names3 = &names;
-        let variant_exprs = names.iter().zip(original_types.iter())
-            .map(|(name, typ)|
-                 if is_custom_type(typ) {
-                     quote! {
-                         match ::std::str::FromStr::from_str(&#name) {
-                             Ok(custom_set) => custom_set,
-                             Err(error) => return Err(::mg_settings::errors::Error::Setting(error)),
-                         }
-                     }
-                 }
-                 else {
-                     quote! { #name }
-                 }
-            );
-        let types1 = &types;
-        let type_names = types.iter()
-            .map(|ident| value_type_to_type(&ident));
 
         let to_variant_fn_variant = quote! {
-            #(#string_names => {
-                if let ::mg_settings::Value::#types1(#names1) = value {
-                    Ok(#capitalized_names(#variant_exprs))
-                }
-                else {
-                    Err(::mg_settings::errors::Error::Setting(
-                        ::mg_settings::errors::SettingError::WrongType {
-                            actual: value.to_type().to_string(),
-                            expected: #type_names.to_string(),
-                        }
-                    ).into())
-                }
-            },)*
+            #(#string_names => #conversions
+                .map(#capitalized_names)
+                .map_err(::mg_settings::errors::Error::Setting),)*
+        };
+
+        let unknown_setting = quote! {
+            _ => Err(::mg_settings::settings::unknown_setting::<Self>(name)),
         };
 
+        let nested_to_variant = to_nested_arms(&nested_fields, &quote! { to_variant(nested_name, value) }, false, true);
         let to_variant_fn = quote! {
             #[allow(unknown_lints, cyclomatic_complexity)]
             fn to_variant(name: &str, value: ::mg_settings::Value)
@@ -235,8 +390,134 @@ fn to_settings_impl(name: &Ident, variant_name: &Ident, settings_struct: &Data)
             {
                 match name {
                     #to_variant_fn_variant
-                    _ => Err(::mg_settings::errors::Error::Setting(
-                        ::mg_settings::errors::SettingError::UnknownSetting(name.to_string())).into()),
+                    #nested_to_variant
+                    #unknown_setting
+                }
+            }
+        };
+
+        let nested_variants2 = nested_fields.iter().map(|field| &field.variant);
+        let nested_idents = nested_fields.iter().map(|field| &field.ident);
+        let nested_flag_variant = to_nested_arms(&nested_fields, &quote! { flag_variant(nested_name) }, false, true);
+        let no_prefixes = nested_fields.iter().map(|field| format!("no{}.", field.name));
+        let no_prefixes = &no_prefixes.collect::<Vec<_>>();
+        let no_prefixes2 = no_prefixes;
+        let nested_field_names = nested_fields.iter().map(|field| &field.name);
+        let nested_types = nested_fields.iter().map(|field| &field.typ);
+        let nested_variants = nested_fields.iter().map(|field| &field.variant);
+        let flag_variant_fn = quote! {
+            #[allow(unreachable_patterns)]
+            fn flag_variant(name: &str) -> ::mg_settings::errors::Result<Self::Variant> {
+                match name {
+                    #(#flag_names => Ok(#flag_variants),)*
+                    #(#non_flag_names => Err(::mg_settings::errors::Error::Setting(
+                        ::mg_settings::errors::SettingError::WrongType {
+                            actual: #non_flag_types.to_string(),
+                            expected: "bool".to_string(),
+                        })),)*
+                    #nested_flag_variant
+                    // The `no` prefix of `set nohints.enabled` applies to the nested setting name.
+                    #(_ if name.starts_with(#no_prefixes) => {
+                        let nested_name = format!("no{}", &name[#no_prefixes2.len()..]);
+                        <#nested_types as ::mg_settings::settings::Settings>::flag_variant(&nested_name)
+                            .map(#nested_variants)
+                            .map_err(|error| ::mg_settings::settings::nested_error(#nested_field_names, error))
+                    },)*
+                    #unknown_setting
+                }
+            }
+        };
+
+        let types1 = &types;
+        let types2 = &types;
+
+        let nested_get_value = to_nested_arms(&nested_fields, &quote! { get_value(nested_name) }, true, false);
+        let get_value_fn = quote! {
+            fn get_value(&self, name: &str) -> ::mg_settings::errors::Result<::mg_settings::Value> {
+                match name {
+                    #(#string_names => Ok(<#types1 as ::mg_settings::SettingValue>::to_value(&self.#names1)),)*
+                    #nested_get_value
+                    #unknown_setting
+                }
+            }
+        };
+
+        let nested_alias_types = nested_fields.iter().map(|field| &field.typ);
+        let nested_alias_prefixes = nested_fields.iter().map(|field| format!("{}.", field.name));
+        let aliases_fn = quote! {
+            fn aliases() -> Vec<::mg_settings::settings::SettingAlias> {
+                #[allow(unused_mut)]
+                let mut aliases = vec![#(#aliases),*];
+                #(
+                    let prefix = #nested_alias_prefixes;
+                    aliases.extend(<#nested_alias_types as ::mg_settings::settings::Settings>::aliases().into_iter()
+                        .map(|alias| ::mg_settings::settings::SettingAlias {
+                            alias: format!("{}{}", prefix, alias.alias),
+                            name: format!("{}{}", prefix, alias.name),
+                            ..alias
+                        }));
+                )*
+                aliases
+            }
+        };
+
+        let setting_names_fn = quote! {
+            fn setting_names() -> Vec<String> {
+                let mut names = vec![];
+                #(#setting_names)*
+                names
+            }
+        };
+
+        let nested_setting_type = to_nested_arms(&nested_fields, &quote! { setting_type(nested_name) }, false, false);
+        let setting_type_fn = quote! {
+            fn setting_type(name: &str) -> ::mg_settings::errors::Result<::mg_settings::ValueType> {
+                match name {
+                    #(#string_names => Ok(<#types2 as ::mg_settings::SettingValue>::value_type()),)*
+                    #nested_setting_type
+                    #unknown_setting
+                }
+            }
+        };
+
+        let nested_get_variant = to_nested_arms(&nested_fields, &quote! { get_variant(nested_name) }, true, true);
+        let get_variant_fn = quote! {
+            fn get_variant(&self, name: &str) -> ::mg_settings::errors::Result<Self::Variant> {
+                match name {
+                    #(#string_names => Ok(#capitalized_names(self.#names1.clone())),)*
+                    #nested_get_variant
+                    #unknown_setting
+                }
+            }
+        };
+
+        let nested_toggle_variant = to_nested_arms(&nested_fields, &quote! { toggle_variant(nested_name) }, true, true);
+        let toggle_variant_fn = quote! {
+            fn toggle_variant(&self, name: &str) -> ::mg_settings::errors::Result<Self::Variant> {
+                match name {
+                    #(#string_names => {
+                        let result: ::std::result::Result<_, ::mg_settings::errors::SettingError> = #toggles;
+                        result.map_err(::mg_settings::errors::Error::Setting)
+                    },)*
+                    #nested_toggle_variant
+                    #unknown_setting
+                }
+            }
+        };
+
+        let nested_update_variant =
+            to_nested_arms(&nested_fields, &quote! { update_variant(nested_name, operator, value) }, true, true);
+        let update_variant_fn = quote! {
+            #[allow(unknown_lints, cyclomatic_complexity, unused_variables)]
+            fn update_variant(&self, name: &str, operator: ::mg_settings::SetOperator, value: ::mg_settings::Value)
+                -> ::mg_settings::errors::Result<Self::Variant>
+            {
+                match name {
+                    #(#string_names => #updates
+                        .map(#capitalized_names)
+                        .map_err(::mg_settings::errors::Error::Setting),)*
+                    #nested_update_variant
+                    #unknown_setting
                 }
             }
         };
@@ -245,13 +526,30 @@ fn to_settings_impl(name: &Ident, variant_name: &Ident, settings_struct: &Data)
             impl ::mg_settings::settings::Settings for #name {
                 type Variant = #variant_name;
 
+                #aliases_fn
+
+                #flag_variant_fn
+
+                #get_value_fn
+
+                #get_variant_fn
+
+                #setting_names_fn
+
+                #setting_type_fn
+
                 #to_variant_fn
 
+                #toggle_variant_fn
+
+                #update_variant_fn
+
                 fn set_value(&mut self, value: Self::Variant) {
                     match value {
                         #(#capitalized_names(#names1) => {
                             self.#names2 = #names3
-                        }),*
+                        },)*
+                        #(#nested_variants2(value) => self.#nested_idents.set_value(value),)*
                     }
                 }
             }
@@ -262,12 +560,111 @@ fn to_settings_impl(name: &Ident, variant_name: &Ident, settings_struct: &Data)
     }
 }
 
+/// Get the other names of `field` from its `#[alias = "old-name"]` and
+/// `#[settings(renamed_from = "old-name", note = "...")]` attributes, along with whether they are
+/// deprecated and the note of the deprecation.
+/// The `deprecated` attribute cannot be used since rustc only allows the `since` and `note` arguments:
+/// using `renamed_from` in it gives an error pointing to the `settings` attribute.
+fn to_aliases(field: &Field) -> Vec<(String, bool, Option<String>)> {
+    let mut aliases = vec![];
+    for attribute in &field.attrs {
+        match attribute.interpret_meta() {
+            Some(NameValue(MetaNameValue { ref ident, lit: Lit::Str(ref alias), .. })) if ident == "alias" =>
+                aliases.push((alias.value(), false, None)),
+            Some(List(MetaList { ref ident, ref nested, .. })) if ident == "deprecated" => {
+                let renamed = nested.iter().any(|argument|
+                    matches!(*argument, Meta(NameValue(MetaNameValue { ref ident, .. })) if ident == "renamed_from"));
+                if renamed {
+                    panic!("`renamed_from` is not supported in the `deprecated` attribute, use \
+                            `#[settings(renamed_from = \"name\", note = \"text\")]` instead");
+                }
+            },
+            Some(List(MetaList { ref ident, ref nested, .. })) if ident == "settings" => {
+                let mut renamed_from = None;
+                let mut note = None;
+                for argument in nested {
+                    match *argument {
+                        Meta(NameValue(MetaNameValue { ref ident, lit: Lit::Str(ref value), .. })) if ident == "renamed_from" =>
+                            renamed_from = Some(value.value()),
+                        Meta(NameValue(MetaNameValue { ref ident, lit: Lit::Str(ref value), .. })) if ident == "note" =>
+                            note = Some(value.value()),
+                        Meta(Word(ref ident)) if ident == "nested" => (),
+                        _ => panic!("Unexpected `{:?}`, expecting `nested`, `renamed_from = \"name\"` or `note = \"text\"`",
+                                    argument),
+                    }
+                }
+                if let Some(renamed_from) = renamed_from {
+                    aliases.push((renamed_from, true, note));
+                }
+            },
+            _ => (),
+        }
+    }
+    aliases
+}
+
+/// A field containing nested settings, marked with `#[settings(nested)]`.
+struct NestedField {
+    ident: Ident,
+    name: String,
+    typ: Type,
+    variant: Tokens,
+}
+
+/// Create the match arms for the dotted names of the nested settings, like `hints.chars`, which call the
+/// method `call` of the nested settings with the rest of the name, named `nested_name`.
+/// The method is called on the field if `is_method`, and its result is wrapped in the variant of the
+/// field if `is_variant`.
+fn to_nested_arms(nested_fields: &[NestedField], call: &Tokens, is_method: bool, is_variant: bool) -> Tokens {
+    let arms = nested_fields.iter()
+        .map(|field| {
+            let NestedField { ref ident, ref name, ref typ, ref variant } = *field;
+            let prefix = format!("{}.", name);
+            let receiver =
+                if is_method {
+                    quote! { self.#ident. }
+                }
+                else {
+                    quote! { <#typ as ::mg_settings::settings::Settings>:: }
+                };
+            let map_variant =
+                if is_variant {
+                    quote! { .map(#variant) }
+                }
+                else {
+                    quote! {}
+                };
+            quote! {
+                _ if name.starts_with(#prefix) => {
+                    let nested_name = &name[#prefix.len()..];
+                    #receiver #call
+                        #map_variant
+                        .map_err(|error| ::mg_settings::settings::nested_error(#name, error))
+                },
+            }
+        });
+    quote! {
+        #(#arms)*
+    }
+}
+
 /// Create the function returning the completion of the setting values.
 pub fn to_setting_completion_fn(name: &Ident, body: &Data) -> Tokens {
     let mut completions = vec![];
+    let mut nested_completions = vec![];
     if let Struct(DataStruct { fields: Fields::Named(ref fields), .. }) = *body {
         'field_loop:
         for field in &fields.named {
+            if is_nested(&field.attrs) {
+                let prefix = format!("{}.", field.ident.as_ref().unwrap().to_string().replace('_', "-"));
+                let field_type = &field.ty;
+                nested_completions.push(quote! {
+                    vec.extend(<#field_type as ::mg_settings::SettingCompletion>::get_value_completions().into_iter()
+                        .map(|(name, values)| (format!("{}{}", #prefix, name), values)));
+                });
+                continue;
+            }
+
             for attribute in &field.attrs {
                 if let Some(List(MetaList { ref ident, ref nested, .. })) = attribute.interpret_meta() {
                     if ident.as_ref() == "completion" {
@@ -283,18 +680,24 @@ pub fn to_setting_completion_fn(name: &Ident, body: &Data) -> Tokens {
             let setting_name = field.ident.as_ref().unwrap().to_string().replace('_', "-");
             let field_type = &field.ty;
 
-            completions.push(quote! {
-                (#setting_name.to_string(), #field_type::completion_values())
-            });
+            if let Some(choices) = one_of_choices(field) {
+                completions.push(quote! {
+                    (#setting_name.to_string(), vec![#(#choices.to_string()),*])
+                });
+            }
+            else {
+                completions.push(quote! {
+                    (#setting_name.to_string(), <#field_type as ::mg_settings::CompletionValues>::completion_values())
+                });
+            }
         }
     }
 
     quote! {
-        use mg_settings::CompletionValues;
-
         impl ::mg_settings::SettingCompletion for #name {
             fn get_value_completions() -> ::std::collections::HashMap<String, Vec<String>> {
                 let mut vec = vec![#(#completions),*];
+                #(#nested_completions)*
                 let iter = vec.drain(..);
                 iter.collect()
             }
@@ -302,25 +705,477 @@ pub fn to_setting_completion_fn(name: &Ident, body: &Data) -> Tokens {
     }
 }
 
-/// Convert a Rust type to a `Value` type.
-fn to_value_type(ident: &Ident) -> Ident {
-    let value_type =
-        match ident.to_string().as_ref() {
-            "bool" => "Bool",
-            "f64" => "Float",
-            "i64" => "Int",
-            _ => "Str",
+/// Get the generic type argument of `typ` if its name is `name`, like `T` in `Vec<T>`.
+fn generic_argument<'a>(typ: &'a Type, name: &str) -> Option<&'a Type> {
+    if let Type::Path(TypePath { ref path, .. }) = *typ {
+        if let Some(segment) = path.segments.iter().last() {
+            if segment.ident.as_ref() == name {
+                if let PathArguments::AngleBracketed(ref arguments) = segment.arguments {
+                    return arguments.args.iter()
+                        .filter_map(|argument|
+                            if let GenericArgument::Type(ref typ) = *argument {
+                                Some(typ)
+                            }
+                            else {
+                                None
+                            })
+                        .last();
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Get the name of the last path segment of a type, like `String` in `std::string::String`.
+fn type_ident(typ: &Type) -> Option<&Ident> {
+    if let Type::Path(TypePath { ref path, .. }) = *typ {
+        path.segments.iter().last().map(|segment| &segment.ident)
+    }
+    else {
+        None
+    }
+}
+
+/// Get the human-readable name of a type, as shown in the errors.
+fn type_name(typ: &Type) -> String {
+    if let Some(inner_type) = generic_argument(typ, "Option") {
+        return format!("{} or none", type_name(inner_type));
+    }
+    if let Some(element_type) = generic_argument(typ, "Vec") {
+        return format!("list of {}", type_name(element_type));
+    }
+    if let Some(value_type) = map_value_type(typ) {
+        return format!("map of {}", type_name(value_type));
+    }
+    match type_ident(typ).map(|ident| ident.to_string()) {
+        Some(ref ident) if ident == "bool" => "bool".to_string(),
+        Some(ref ident) if ident == "char" => "character".to_string(),
+        Some(ref ident) if ident == "PathBuf" => "path".to_string(),
+        Some(ref ident) if FLOAT_TYPES.contains(&ident.as_str()) => "float".to_string(),
+        Some(ref ident) if INTEGER_TYPES.contains(&ident.as_str()) => "integer".to_string(),
+        _ => "string".to_string(),
+    }
+}
+
+/// Get the value type of a `HashMap<String, T>` or `BTreeMap<String, T>`.
+fn map_value_type(typ: &Type) -> Option<&Type> {
+    generic_argument(typ, "HashMap")
+        .or_else(|| generic_argument(typ, "BTreeMap"))
+}
+
+/// Create the closure checking the value of `field` according to its validation attributes:
+/// `#[range(min = 25, max = 500)]`, `#[non_empty]`, `#[one_of("a", "b")]` and `#[validate = "path::to::fn"]`,
+/// where the function has the signature `fn(&T) -> Result<(), String>`.
+/// Return `None` if the field has no validation attribute.
+fn to_validation(field: &Field) -> Option<Tokens> {
+    let typ = &field.ty;
+    // The range and choice checks apply to the inner value of an optional setting.
+    let inner_type = generic_argument(typ, "Option").unwrap_or(typ);
+    let mut inner_checks = vec![];
+    let mut checks = vec![];
+    for attribute in &field.attrs {
+        match attribute.interpret_meta() {
+            Some(List(MetaList { ref ident, ref nested, .. })) if ident == "range" => {
+                let mut min = None;
+                let mut max = None;
+                for argument in nested {
+                    match *argument {
+                        Meta(NameValue(MetaNameValue { ref ident, ref lit, .. })) if ident == "min" =>
+                            min = Some(to_default_value(inner_type, lit)),
+                        Meta(NameValue(MetaNameValue { ref ident, ref lit, .. })) if ident == "max" =>
+                            max = Some(to_default_value(inner_type, lit)),
+                        _ => panic!("Unexpected `{:?}`, expecting `min = value` or `max = value`", argument),
+                    }
+                }
+                let min_string = min.as_ref().map(|min| quote! { Some((#min).to_string()) })
+                    .unwrap_or_else(|| quote! { None });
+                let max_string = max.as_ref().map(|max| quote! { Some((#max).to_string()) })
+                    .unwrap_or_else(|| quote! { None });
+                let out_of_range = quote! {
+                    return Err(::mg_settings::errors::SettingError::OutOfRange {
+                        actual: value.to_string(),
+                        min: #min_string,
+                        max: #max_string,
+                    });
+                };
+                if let Some(min) = min {
+                    inner_checks.push(quote! {
+                        if *value < #min {
+                            #out_of_range
+                        }
+                    });
+                }
+                if let Some(max) = max {
+                    inner_checks.push(quote! {
+                        if *value > #max {
+                            #out_of_range
+                        }
+                    });
+                }
+            },
+            Some(List(MetaList { ref ident, .. })) if ident == "one_of" => {
+                let choices = one_of_choices(field).unwrap_or_default();
+                inner_checks.push(quote! {
+                    let choices = [#(#choices),*];
+                    if !choices.contains(&::std::convert::AsRef::<str>::as_ref(value)) {
+                        return Err(::mg_settings::errors::SettingError::unknown_choice(value, choices.to_vec()));
+                    }
+                });
+            },
+            Some(Word(ref ident)) if ident == "non_empty" => {
+                let is_empty =
+                    if type_ident(typ).map(|ident| ident == "PathBuf").unwrap_or(false) {
+                        quote! { value.as_os_str().is_empty() }
+                    }
+                    else {
+                        quote! { value.is_empty() }
+                    };
+                checks.push(quote! {
+                    if #is_empty {
+                        return Err(::mg_settings::errors::SettingError::Invalid("the value cannot be empty".to_string()));
+                    }
+                });
+            },
+            Some(NameValue(MetaNameValue { ref ident, lit: Lit::Str(ref function), .. })) if ident == "validate" => {
+                let function: Path = syn::parse_str(&function.value())
+                    .unwrap_or_else(|_| panic!("Invalid validation function `{}`, expecting a path", function.value()));
+                checks.push(quote! {
+                    #function(value).map_err(::mg_settings::errors::SettingError::Invalid)?;
+                });
+            },
+            _ => (),
+        }
+    }
+
+    if !inner_checks.is_empty() {
+        if generic_argument(typ, "Option").is_some() {
+            checks.push(quote! {
+                if let Some(ref value) = *value {
+                    #(#inner_checks)*
+                }
+            });
+        }
+        else {
+            checks.extend(inner_checks);
+        }
+    }
+
+    if checks.is_empty() {
+        return None;
+    }
+
+    Some(quote! {
+        |value| {
+            {
+                let value: &#typ = &value;
+                #(#checks)*
+            }
+            Ok(value)
+        }
+    })
+}
+
+/// Get the choices of the `#[one_of("a", "b")]` attribute of `field`, if any.
+fn one_of_choices(field: &Field) -> Option<Vec<String>> {
+    for attribute in &field.attrs {
+        if let Some(List(MetaList { ref ident, ref nested, .. })) = attribute.interpret_meta() {
+            if ident == "one_of" {
+                let choices = nested.iter()
+                    .map(|choice|
+                        match *choice {
+                            NestedMeta::Literal(Lit::Str(ref choice)) => choice.value(),
+                            _ => panic!("Unexpected `{:?}`, expecting a string", choice),
+                        })
+                    .collect();
+                return Some(choices);
+            }
+        }
+    }
+    None
+}
+
+/// Create the expression applying the `SetOperator` named `operator` with the `Value` named `value` to
+/// the reference `current` to the current value of type `typ`, or `None` if this type does not support
+/// the set operators.
+fn to_update(typ: &Type) -> Option<Tokens> {
+    let operator = quote! { ::mg_settings::SetOperator };
+
+    if let Some(element_type) = generic_argument(typ, "Vec") {
+        // The value is either a list of elements or a single element.
+        let element_conversion = to_conversion(element_type);
+        return Some(quote! {{
+            let values: ::std::result::Result<Vec<_>, ::mg_settings::errors::SettingError> =
+                match value {
+                    ::mg_settings::Value::List(values) =>
+                        values.into_iter()
+                            .enumerate()
+                            .map(|(index, value)|
+                                (#element_conversion).map_err(|error|
+                                    ::mg_settings::errors::SettingError::InvalidElement {
+                                        index,
+                                        error: Box::new(error),
+                                    }))
+                            .collect(),
+                    value => (#element_conversion).map(|value| vec![value]),
+                };
+            values.map(|values| {
+                let mut current = current.clone();
+                match operator {
+                    #operator::Add => current.extend(values),
+                    #operator::Prepend => {
+                        current.splice(0..0, values);
+                    },
+                    #operator::Subtract => current.retain(|element| !values.contains(element)),
+                }
+                current
+            })
+        }});
+    }
+
+    if map_value_type(typ).is_some() {
+        // Insert a map of values, or remove a key or a list of keys.
+        let conversion = to_conversion(typ);
+        let wrong_key_type = quote! {
+            value => Err(::mg_settings::errors::SettingError::WrongType {
+                actual: value.to_type().to_string(),
+                expected: "string".to_string(),
+            }),
         };
-    Ident::from(value_type)
+        return Some(quote! {
+            match operator {
+                #operator::Add | #operator::Prepend => {
+                    let values: ::std::result::Result<#typ, ::mg_settings::errors::SettingError> = #conversion;
+                    values.map(|values| {
+                        let mut current = current.clone();
+                        current.extend(values);
+                        current
+                    })
+                },
+                #operator::Subtract => {
+                    let keys: ::std::result::Result<Vec<String>, ::mg_settings::errors::SettingError> =
+                        match value {
+                            ::mg_settings::Value::List(keys) =>
+                                keys.into_iter()
+                                    .map(|key|
+                                        match key {
+                                            ::mg_settings::Value::Str(key) => Ok(key),
+                                            #wrong_key_type
+                                        })
+                                    .collect(),
+                            ::mg_settings::Value::Str(key) => Ok(vec![key]),
+                            #wrong_key_type
+                        };
+                    keys.map(|keys| {
+                        let mut current = current.clone();
+                        for key in keys {
+                            current.remove(&key);
+                        }
+                        current
+                    })
+                },
+            }
+        });
+    }
+
+    let conversion = to_conversion(typ);
+    match type_ident(typ).map(|ident| ident.to_string()) {
+        Some(ref ident) if FLOAT_TYPES.contains(&ident.as_str()) => Some(quote! {
+            (#conversion).map(|value|
+                match operator {
+                    #operator::Add => *current + value,
+                    #operator::Prepend => *current * value,
+                    #operator::Subtract => *current - value,
+                })
+        }),
+        Some(ref ident) if INTEGER_TYPES.contains(&ident.as_str()) => Some(quote! {
+            (#conversion).and_then(|value| {
+                let (result, symbol) =
+                    match operator {
+                        #operator::Add => (current.checked_add(value), "+"),
+                        #operator::Prepend => (current.checked_mul(value), "*"),
+                        #operator::Subtract => (current.checked_sub(value), "-"),
+                    };
+                // The result does not fit in the type: show the operation instead.
+                result.ok_or_else(|| ::mg_settings::errors::SettingError::OutOfRange {
+                    actual: format!("{} {} {}", current, symbol, value),
+                    min: Some(<#typ>::MIN.to_string()),
+                    max: Some(<#typ>::MAX.to_string()),
+                })
+            })
+        }),
+        Some(ref ident) if ident == "String" => Some(quote! {
+            (#conversion).map(|value: String|
+                match operator {
+                    #operator::Add => format!("{}{}", current, value),
+                    #operator::Prepend => format!("{}{}", value, current),
+                    #operator::Subtract => current.replacen(value.as_str(), "", 1),
+                })
+        }),
+        _ => None,
+    }
 }
 
-/// Convert a `Value` type to a string representation of the type.
-fn value_type_to_type(ident: &Ident) -> &str {
-    match ident.to_string().as_ref() {
-        "Bool" => "bool",
-        "Float" => "float",
-        "Int" => "integer",
-        "Str" => "string",
-        ty => panic!("Unknown Value type {}", ty),
+/// Create the expression converting the `Value` named `value` to a `Result` of the Rust type `typ`.
+fn to_conversion(typ: &Type) -> Tokens {
+    let wrong_type = |expected: String| quote! {
+        value => Err(::mg_settings::errors::SettingError::WrongType {
+            actual: value.to_type().to_string(),
+            expected: #expected.to_string(),
+        }),
+    };
+
+    if let Some(inner_type) = generic_argument(typ, "Option") {
+        let inner_conversion = to_conversion(inner_type);
+        return quote! {
+            match value {
+                ::mg_settings::Value::Str(ref string) if string == "none" => Ok(None),
+                value => (#inner_conversion).map(Some),
+            }
+        };
+    }
+
+    if let Some(element_type) = generic_argument(typ, "Vec") {
+        let element_conversion = to_conversion(element_type);
+        let wrong_type = wrong_type(type_name(typ));
+        return quote! {
+            match value {
+                ::mg_settings::Value::List(values) =>
+                    values.into_iter()
+                        .enumerate()
+                        .map(|(index, value)|
+                            (#element_conversion).map_err(|error|
+                                ::mg_settings::errors::SettingError::InvalidElement {
+                                    index,
+                                    error: Box::new(error),
+                                }))
+                        .collect(),
+                #wrong_type
+            }
+        };
+    }
+
+    if let Some(value_type) = map_value_type(typ) {
+        let value_conversion = to_conversion(value_type);
+        let wrong_type = wrong_type(type_name(typ));
+        return quote! {
+            match value {
+                ::mg_settings::Value::Map(values) =>
+                    values.into_iter()
+                        .map(|(key, value)|
+                            match #value_conversion {
+                                Ok(value) => Ok((key, value)),
+                                Err(error) => Err(::mg_settings::errors::SettingError::InvalidKey {
+                                    key,
+                                    error: Box::new(error),
+                                }),
+                            })
+                        .collect(),
+                #wrong_type
+            }
+        };
+    }
+
+    let wrong_type = wrong_type(type_name(typ));
+    let out_of_range = quote! {
+        ::mg_settings::errors::SettingError::OutOfRange {
+            actual: value.to_string(),
+            min: Some(<#typ>::MIN.to_string()),
+            max: Some(<#typ>::MAX.to_string()),
+        }
+    };
+    let value_type =
+        match type_ident(typ).map(|ident| ident.to_string()) {
+            Some(ref ident) if ident == "bool" => "Bool",
+            Some(ref ident) if ident == "i64" => "Int",
+            Some(ref ident) if ident == "String" => "Str",
+            Some(ref ident) if ident == "char" => {
+                return quote! {
+                    match value {
+                        ::mg_settings::Value::Str(value) => {
+                            let mut chars = value.chars();
+                            match (chars.next(), chars.next()) {
+                                (Some(character), None) => Ok(character),
+                                _ => Err(::mg_settings::errors::SettingError::WrongType {
+                                    actual: "string".to_string(),
+                                    expected: "character".to_string(),
+                                }),
+                            }
+                        },
+                        #wrong_type
+                    }
+                };
+            },
+            Some(ref ident) if ident == "f64" => {
+                // An integer literal is a valid float.
+                return quote! {
+                    match value {
+                        ::mg_settings::Value::Float(value) => Ok(value),
+                        ::mg_settings::Value::Int(value) => Ok(value as f64),
+                        #wrong_type
+                    }
+                };
+            },
+            Some(ref ident) if FLOAT_TYPES.contains(&ident.as_str()) => {
+                return quote! {
+                    match value {
+                        ::mg_settings::Value::Float(value) =>
+                            if <#typ>::MIN as f64 <= value && value <= <#typ>::MAX as f64 {
+                                Ok(value as #typ)
+                            }
+                            else {
+                                Err(::mg_settings::errors::SettingError::OutOfRange {
+                                    actual: format!("{:e}", value),
+                                    min: Some(format!("{:e}", <#typ>::MIN)),
+                                    max: Some(format!("{:e}", <#typ>::MAX)),
+                                })
+                            },
+                        ::mg_settings::Value::Int(value) => Ok(value as #typ),
+                        #wrong_type
+                    }
+                };
+            },
+            Some(ref ident) if ident == "i128" => {
+                return quote! {
+                    match value {
+                        ::mg_settings::Value::Int(value) => Ok(i128::from(value)),
+                        #wrong_type
+                    }
+                };
+            },
+            Some(ref ident) if INTEGER_TYPES.contains(&ident.as_str()) => {
+                return quote! {
+                    match value {
+                        ::mg_settings::Value::Int(value) =>
+                            <#typ as ::std::convert::TryFrom<i64>>::try_from(value)
+                                .map_err(|_| #out_of_range),
+                        #wrong_type
+                    }
+                };
+            },
+            Some(ref ident) if ident == "PathBuf" => {
+                return quote! {
+                    match value {
+                        ::mg_settings::Value::Str(value) => Ok(::mg_settings::settings::expand_tilde(&value)),
+                        #wrong_type
+                    }
+                };
+            },
+            _ => {
+                // Custom type (including enum).
+                return quote! {
+                    match value {
+                        ::mg_settings::Value::Str(value) => ::std::str::FromStr::from_str(&value),
+                        #wrong_type
+                    }
+                };
+            },
+        };
+    let value_type = Ident::from(value_type);
+    quote! {
+        match value {
+            ::mg_settings::Value::#value_type(value) => Ok(value),
+            #wrong_type
+        }
     }
 }
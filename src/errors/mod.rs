@@ -21,23 +21,37 @@
 
 //! Parse and io error type.
 
+pub mod key;
+pub mod render;
 pub mod settings;
 
+use std::error;
 use std::fmt::{self, Display, Formatter};
 use std::io;
+use std::path::PathBuf;
 use std::result;
 
-use position::Pos;
+use position::{Pos, Span, Spanned};
+pub use self::key::KeyError;
+pub use self::render::Renderer;
 pub use self::settings::SettingError;
-use self::Error::{Msg, Parse, Setting};
+use self::Error::{Io, Msg, Parse, Setting};
+use self::ErrorType::RecursiveInclude;
 
 /// Parser result type.
 pub type Result<T> = result::Result<T, Error>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 /// Parser or setting error.
 pub enum Error {
-    /// Other errors like input/output error.
+    /// Input/output error when opening or reading a file.
+    Io {
+        /// The path of the file, or `None` when the input does not come from a file.
+        path: Option<PathBuf>,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// Other errors.
     Msg(String),
     /// Parse error.
     Parse(ParseError),
@@ -48,6 +62,9 @@ pub enum Error {
 impl Display for Error {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         match *self {
+            Io { path: Some(ref path), ref source } =>
+                write!(formatter, "cannot access file `{}`: {}", path.display(), source),
+            Io { path: None, ref source } => write!(formatter, "{}", source),
             Msg(ref msg) => write!(formatter, "{}", msg),
             Parse(ref error) => write!(formatter, "{}", error),
             Setting(ref error) => write!(formatter, "{}", error),
@@ -55,37 +72,101 @@ impl Display for Error {
     }
 }
 
-impl<'a> Into<Error> for &'a str {
-    fn into(self) -> Error {
-        Msg(self.to_string())
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Io { ref source, .. } => Some(source),
+            Msg(_) => None,
+            Parse(ref error) => Some(error),
+            Setting(ref error) => Some(error),
+        }
+    }
+}
+
+/// The input/output errors are equal when they have the same path and kind.
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        match (self, other) {
+            (Io { path: path1, source: source1 }, Io { path: path2, source: source2 }) =>
+                path1 == path2 && source1.kind() == source2.kind(),
+            (Msg(msg1), Msg(msg2)) => msg1 == msg2,
+            (Parse(error1), Parse(error2)) => error1 == error2,
+            (Setting(error1), Setting(error2)) => error1 == error2,
+            _ => false,
+        }
+    }
+}
+
+impl From<&str> for Error {
+    fn from(msg: &str) -> Self {
+        Msg(msg.to_string())
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Io {
+            path: None,
+            source: error,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Parse(error)
     }
 }
 
-impl Into<Error> for io::Error {
-    fn into(self) -> Error {
-        Msg(self.to_string())
+impl From<SettingError> for Error {
+    fn from(error: SettingError) -> Self {
+        Setting(error)
     }
 }
 
 /// A set of error types that can occur parsing the settings file.
 #[derive(Debug, PartialEq)]
 pub enum ErrorType {
+    /// The maximum include depth was exceeded.
+    IncludeDepth,
     /// A missing argument.
     MissingArgument,
     /// No command (or a comment) was entered.
     NoCommand,
     /// Parse error.
     Parse,
+    /// A file includes itself, directly or through other files.
+    /// The chain of included files, ending with the file included again, is attached.
+    RecursiveInclude(Vec<PathBuf>),
     /// Unknown command.
     UnknownCommand,
 }
 
+impl ErrorType {
+    /// Get the stable code of this error type, used in diagnostics.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            ErrorType::IncludeDepth => "include-depth",
+            ErrorType::MissingArgument => "missing-argument",
+            ErrorType::NoCommand => "no-command",
+            ErrorType::Parse => "parse",
+            RecursiveInclude(_) => "recursive-include",
+            ErrorType::UnknownCommand => "unknown-command",
+        }
+    }
+}
+
 /// Struct which holds information about an error at a specific position.
 #[derive(Debug, PartialEq)]
 pub struct ParseError {
     /// The expected token.
     pub expected: String,
     pos: Pos,
+    /// The location of the error, including the file it comes from.
+    /// This is filled by the parser.
+    pub span: Option<Box<Span>>,
+    /// A likely replacement of the unexpected token, like the closest command name for an unknown command.
+    pub suggestion: Option<String>,
     /// The error type.
     pub typ: ErrorType,
     /// The unexpected token.
@@ -96,17 +177,149 @@ impl ParseError {
     /// Create a new error.
     #[allow(unknown_lints, new_ret_no_self)]
     pub fn new(typ: ErrorType, unexpected: String, expected: String, pos: Pos) -> Error {
+        ParseError::new_with_suggestion(typ, unexpected, expected, pos, None)
+    }
+
+    /// Create a new error with a suggested replacement of the unexpected token.
+    pub fn new_with_suggestion(typ: ErrorType, unexpected: String, expected: String, pos: Pos,
+                               suggestion: Option<String>) -> Error
+    {
         Error::Parse(ParseError {
-            expected: expected,
-            pos: pos,
-            typ: typ,
-            unexpected: unexpected,
+            expected,
+            pos,
+            span: None,
+            suggestion,
+            typ,
+            unexpected,
         })
     }
+
+    /// Get the description of the error, without its position.
+    pub fn message(&self) -> String {
+        if let RecursiveInclude(ref chain) = self.typ {
+            let chain: Vec<_> = chain.iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            return format!("recursive include: {}", chain.join(" -> "));
+        }
+        let mut message = format!("unexpected {}, expecting {}", self.unexpected, self.expected);
+        if let Some(ref suggestion) = self.suggestion {
+            message.push_str(&format!(", did you mean `{}`?", suggestion));
+        }
+        message
+    }
+
+    /// Get the position of the error.
+    pub fn pos(&self) -> &Pos {
+        &self.pos
+    }
+}
+
+/// The severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// An error: the line was not applied.
+    Error,
+    /// A hint: something that might be intentional, but is worth checking.
+    Hint,
+    /// A warning: the line was applied, but is likely a mistake.
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        let string =
+            match *self {
+                Severity::Error => "error",
+                Severity::Hint => "hint",
+                Severity::Warning => "warning",
+            };
+        write!(formatter, "{}", string)
+    }
+}
+
+/// An error, warning or hint found while parsing.
+///
+/// The codes are stable and can be used to filter the diagnostics:
+///
+///  * errors: `include-depth`, `io`, `missing-argument`, `no-command`, `parse`, `recursive-include`,
+///    `unknown-command`, `other` and the codes of `SettingError::code()`;
+///  * warnings: `deprecated-setting`, `duplicate-setting`, `overridden-mapping`;
+///  * hints: `unmapped-key`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// The stable code identifying the kind of diagnostic.
+    pub code: &'static str,
+    /// The description of the issue.
+    pub message: String,
+    /// The severity of the issue.
+    pub severity: Severity,
+    /// The location of the issue, if any.
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    /// Create a new warning.
+    pub fn warning(code: &'static str, message: String, span: Span) -> Self {
+        Diagnostic {
+            code,
+            message,
+            severity: Severity::Warning,
+            span: Some(span),
+        }
+    }
+
+    /// Create a new hint.
+    pub fn hint(code: &'static str, message: String, span: Span) -> Self {
+        Diagnostic {
+            code,
+            message,
+            severity: Severity::Hint,
+            span: Some(span),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self.span {
+            Some(ref span) => write!(formatter, "{} on {}", self.message, span.start),
+            None => write!(formatter, "{}", self.message),
+        }
+    }
+}
+
+impl From<&Error> for Diagnostic {
+    fn from(error: &Error) -> Self {
+        let (code, message, span) =
+            match *error {
+                Io { .. } => ("io", error.to_string(), None),
+                Msg(ref message) => ("other", message.clone(), None),
+                Parse(ref error) => (error.typ.code(), error.message(), error.span.as_ref().map(|span| (**span).clone())),
+                Setting(ref error) => (error.code(), error.to_string(), None),
+            };
+        Diagnostic {
+            code,
+            message,
+            severity: Severity::Error,
+            span,
+        }
+    }
+}
+
+impl From<&Spanned<Error>> for Diagnostic {
+    fn from(error: &Spanned<Error>) -> Self {
+        let mut diagnostic = Diagnostic::from(&error.node);
+        diagnostic.span = Some(error.span.clone());
+        diagnostic
+    }
 }
 
 impl Display for ParseError {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        write!(formatter, "unexpected {}, expecting {} on {}", self.unexpected, self.expected, self.pos)
+        write!(formatter, "{} on {}", self.message(), self.pos)
     }
 }
+
+impl error::Error for ParseError {
+}
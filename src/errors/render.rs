@@ -0,0 +1,178 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Rendering of the diagnostics with the source line, in the style of rustc:
+//!
+//! ```text
+//! error[parse]: unexpected 2
+//!  --> config:3:15
+//!   |
+//! 3 | set zoom = [1 2]
+//!   |               ^
+//!   = note: expecting , or ]
+//! ```
+
+use std::fmt::Write;
+use std::path::Path;
+
+use ParseResult;
+use position::Span;
+use super::{Diagnostic, Error, Severity};
+use super::ErrorType::RecursiveInclude;
+
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[1;36m";
+const RED: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+const YELLOW: &str = "\x1b[1;33m";
+
+/// Diagnostic renderer.
+pub struct Renderer {
+    /// Whether to use ANSI escape codes to color the output.
+    pub colors: bool,
+    /// The name shown for the text that does not come from a file.
+    pub source_name: String,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer {
+            colors: false,
+            source_name: "<input>".to_string(),
+        }
+    }
+}
+
+impl Renderer {
+    /// Render a diagnostic.
+    /// `sources` gives the text of the file at a path, or the text given to the parser for `None`: the
+    /// source line is not shown when it gives `None`.
+    pub fn render_diagnostic<'a, F>(&self, diagnostic: &Diagnostic, sources: F) -> String
+        where F: Fn(Option<&Path>) -> Option<&'a str>
+    {
+        self.render(diagnostic, &diagnostic.message, None, sources)
+    }
+
+    /// Render an error, with the expected token in a note for the parse errors.
+    pub fn render_error<'a, F>(&self, error: &Error, sources: F) -> String
+        where F: Fn(Option<&Path>) -> Option<&'a str>
+    {
+        let diagnostic = Diagnostic::from(error);
+        match *error {
+            Error::Parse(ref error) if !matches!(error.typ, RecursiveInclude(_)) => {
+                let message = format!("unexpected {}", error.unexpected);
+                let mut note = format!("expecting {}", error.expected);
+                if let Some(ref suggestion) = error.suggestion {
+                    note.push_str(&format!(", did you mean `{}`?", suggestion));
+                }
+                self.render(&diagnostic, &message, Some(&note), sources)
+            },
+            _ => self.render_diagnostic(&diagnostic, sources),
+        }
+    }
+
+    /// Render the errors followed by the warnings and hints of a parse result, separated by empty lines.
+    pub fn render_result<'a, T, F>(&self, result: &ParseResult<T>, sources: F) -> String
+        where F: Fn(Option<&Path>) -> Option<&'a str>
+    {
+        let errors = result.errors.iter()
+            .map(|error| self.render_error(error, &sources));
+        let warnings = result.warnings.iter()
+            .map(|warning| self.render_diagnostic(warning, &sources));
+        errors.chain(warnings)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render<'a, F>(&self, diagnostic: &Diagnostic, message: &str, note: Option<&str>, sources: F) -> String
+        where F: Fn(Option<&Path>) -> Option<&'a str>
+    {
+        let severity_color =
+            match diagnostic.severity {
+                Severity::Error => RED,
+                Severity::Hint => CYAN,
+                Severity::Warning => YELLOW,
+            };
+        let mut output = String::new();
+        let _ = writeln!(output, "{}{}[{}]{}{}: {}{}", self.color(severity_color), diagnostic.severity,
+                         diagnostic.code, self.color(RESET), self.color(BOLD), message, self.color(RESET));
+        let span =
+            match diagnostic.span {
+                Some(ref span) => span,
+                None => {
+                    if let Some(note) = note {
+                        let _ = writeln!(output, "{}={} note: {}", self.color(BLUE), self.color(RESET), note);
+                    }
+                    return output;
+                },
+            };
+        let line_number = span.start.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let source_name =
+            match span.source {
+                Some(ref path) => path.display().to_string(),
+                None => self.source_name.clone(),
+            };
+        let _ = writeln!(output, "{}{}-->{} {}:{}:{}", gutter, self.color(BLUE), self.color(RESET), source_name,
+                         span.start.line, span.start.column);
+        if let Some(line) = source_line(span, sources) {
+            let _ = writeln!(output, "{} {}|{}", gutter, self.color(BLUE), self.color(RESET));
+            let _ = writeln!(output, "{}{} |{} {}", self.color(BLUE), line_number, self.color(RESET), line);
+            // NOTE: the tabs are kept so that the underline is aligned with the text.
+            let indent: String = line.chars()
+                .take(span.start.column.saturating_sub(1))
+                .map(|character| if character == '\t' { '\t' } else { ' ' })
+                .collect();
+            let length =
+                if span.end.line == span.start.line && span.end.column > span.start.column {
+                    span.end.column - span.start.column
+                }
+                else {
+                    1
+                };
+            let _ = writeln!(output, "{} {}|{} {}{}{}{}", gutter, self.color(BLUE), self.color(RESET), indent,
+                             self.color(severity_color), "^".repeat(length), self.color(RESET));
+        }
+        if let Some(note) = note {
+            let _ = writeln!(output, "{} {}={} note: {}", gutter, self.color(BLUE), self.color(RESET), note);
+        }
+        output
+    }
+
+    fn color(&self, color: &'static str) -> &'static str {
+        if self.colors {
+            color
+        }
+        else {
+            ""
+        }
+    }
+}
+
+/// Get the line of `span` from the text of its source.
+fn source_line<'a, F>(span: &Span, sources: F) -> Option<&'a str>
+    where F: Fn(Option<&Path>) -> Option<&'a str>
+{
+    sources(span.source.as_deref())?
+        .lines()
+        .nth(span.start.line.checked_sub(1)?)
+}
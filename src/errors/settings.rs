@@ -21,23 +21,67 @@
 
 //! Settings error type.
 
+use std::error;
 use std::fmt::{self, Display, Formatter};
 
-use self::SettingError::{UnknownChoice, UnknownSetting, WrongType};
+use string::suggest;
+
+use self::SettingError::{Invalid, InvalidElement, InvalidKey, OutOfRange, UnknownChoice, UnknownSetting, UnsupportedOperator, WrongType};
 
 /// Error when getting/setting settings.
 #[allow(missing_docs)]
 #[derive(Debug, PartialEq)]
 pub enum SettingError {
+    /// Invalid value, with a message explaining why.
+    Invalid(String),
+    /// Invalid value for an element of a list setting.
+    /// `Settings::apply` gives the location of the element in the config file.
+    InvalidElement {
+        // The index of the invalid element, starting at 0.
+        index: usize,
+        // The error for the element.
+        error: Box<SettingError>,
+    },
+    /// Invalid value for a key of a map setting.
+    /// `Settings::apply` gives the location of the key in the config file.
+    InvalidKey {
+        // The key of the invalid value.
+        key: String,
+        // The error for the value.
+        error: Box<SettingError>,
+    },
+    /// Value outside of the range of the setting.
+    OutOfRange {
+        // The actual value.
+        actual: String,
+        // The minimum value, if any.
+        min: Option<String>,
+        // The maximum value, if any.
+        max: Option<String>,
+    },
     /// Unknown setting value choice.
     UnknownChoice {
         // The actual value.
         actual: String,
         // The list of expected values.
-        expected: Vec<&'static str>
+        expected: Vec<&'static str>,
+        // The expected value closest to the actual value, if any.
+        suggestion: Option<String>,
     },
     /// Unknown setting name.
-    UnknownSetting(String),
+    UnknownSetting {
+        // The name of the setting.
+        name: String,
+        // The setting name closest to the name, if any.
+        suggestion: Option<String>,
+    },
+    /// Set operator not supported by the type of the setting.
+    UnsupportedOperator {
+        // The operator, like `+=`.
+        operator: String,
+        // The type of the setting.
+        typ: String,
+    },
     /// Wrong value type for setting.
     WrongType {
         // The actual type.
@@ -47,15 +91,89 @@ pub enum SettingError {
     },
 }
 
+impl SettingError {
+    /// Create an unknown choice error, suggesting the expected value closest to `actual`.
+    pub fn unknown_choice(actual: &str, expected: Vec<&'static str>) -> Self {
+        UnknownChoice {
+            actual: actual.to_string(),
+            suggestion: suggest(actual, &expected),
+            expected,
+        }
+    }
+
+    /// Create an unknown setting error, suggesting the name of `names` closest to `name`.
+    pub fn unknown_setting<I>(name: &str, names: I) -> Self
+        where I: IntoIterator,
+              I::Item: AsRef<str>,
+    {
+        UnknownSetting {
+            name: name.to_string(),
+            suggestion: suggest(name, names),
+        }
+    }
+
+    /// Get the stable code of this error, used in diagnostics.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Invalid(_) => "invalid-value",
+            InvalidElement { .. } => "invalid-element",
+            InvalidKey { .. } => "invalid-key",
+            OutOfRange { .. } => "out-of-range",
+            UnknownChoice { .. } => "unknown-choice",
+            UnknownSetting { .. } => "unknown-setting",
+            UnsupportedOperator { .. } => "unsupported-operator",
+            WrongType { .. } => "wrong-type",
+        }
+    }
+}
+
 impl Display for SettingError {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         match *self {
-            UnknownChoice { ref actual, ref expected } =>
-                write!(formatter, "unknown choice {}, expecting one of: {}", actual, expected.join(", ")),
-            UnknownSetting(ref name) =>
-                write!(formatter, "no setting named {}", name),
+            Invalid(ref message) =>
+                write!(formatter, "invalid value: {}", message),
+            InvalidElement { index, ref error } =>
+                write!(formatter, "invalid value at index {}: {}", index, error),
+            InvalidKey { ref key, ref error } =>
+                write!(formatter, "invalid value for key {}: {}", key, error),
+            OutOfRange { ref actual, ref min, ref max } => {
+                write!(formatter, "value {} is out of range, expecting ", actual)?;
+                match (min, max) {
+                    (Some(min), Some(max)) => write!(formatter, "a value between {} and {}", min, max),
+                    (Some(min), None) => write!(formatter, "a value greater than or equal to {}", min),
+                    (None, Some(max)) => write!(formatter, "a value less than or equal to {}", max),
+                    (None, None) => write!(formatter, "a value in range"),
+                }
+            },
+            UnknownChoice { ref actual, ref expected, ref suggestion } => {
+                write!(formatter, "unknown choice {}, expecting one of: {}", actual, expected.join(", "))?;
+                did_you_mean(formatter, suggestion)
+            },
+            UnknownSetting { ref name, ref suggestion } => {
+                write!(formatter, "no setting named {}", name)?;
+                did_you_mean(formatter, suggestion)
+            },
+            UnsupportedOperator { ref operator, ref typ } =>
+                write!(formatter, "operator {} is not supported for a setting of type {}", operator, typ),
             WrongType { ref actual, ref expected } =>
                 write!(formatter, "wrong value type: expecting {}, but found {}", expected, actual),
         }
     }
 }
+
+impl error::Error for SettingError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            InvalidElement { ref error, .. } | InvalidKey { ref error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
+
+/// Write the suggestion, if any.
+fn did_you_mean(formatter: &mut Formatter, suggestion: &Option<String>) -> fmt::Result {
+    match *suggestion {
+        Some(ref suggestion) => write!(formatter, ", did you mean `{}`?", suggestion),
+        None => Ok(()),
+    }
+}
@@ -27,7 +27,9 @@ use errors::{Error, Result};
 pub fn open<P: AsRef<Path>>(path: P) -> Result<File> {
     match File::open(&path) {
         Ok(file) => Ok(file),
-        Err(error) => Err(Error::Msg(format!("failed to open included file `{}`: {}", path.as_ref().to_string_lossy(),
-            error.to_string()))),
+        Err(error) => Err(Error::Io {
+            path: Some(path.as_ref().to_path_buf()),
+            source: error,
+        }),
     }
 }
@@ -0,0 +1,223 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! JSON output of the parse results, for editors and other tools.
+//!
+//! The output is a single line containing an object with these fields:
+//!
+//! ```text
+//! {
+//!   "version": 1,
+//!   "diagnostics": [Diagnostic],
+//!   "commands": [Command]         // only when requested
+//! }
+//!
+//! Diagnostic: {
+//!   "severity": "error" | "warning" | "hint",
+//!   "code": string,               // see errors::Diagnostic
+//!   "message": string,
+//!   "unexpected": string | null,  // the unexpected token, for the parse errors
+//!   "expected": string | null,    // the expected token, for the parse errors
+//!   "suggestion": string | null,
+//!   "span": Span | null
+//! }
+//!
+//! Span: {
+//!   "file": string | null,        // null when the text does not come from a file
+//!   "start": {"line": number, "column": number},
+//!   "end": {"line": number, "column": number},
+//!   "bytes": {"start": number, "end": number}
+//! }
+//!
+//! Command: {
+//!   "command": "app" | "custom" | "flag" | "map" | "query" | "reset" | "set" | "toggle" | "unmap" |
+//!              "update",
+//!   "name": string,               // app, custom, flag, query, reset, set, toggle and update
+//!   "argument": string | null,    // custom
+//!   "count": number | null,       // custom
+//!   "value": Value,               // set and update
+//!   "operator": "+=" | "-=" | "^=", // update
+//!   "mode": string,               // map and unmap
+//!   "keys": string,               // map and unmap, in the key notation like <C-w>v
+//!   "action": string,             // map
+//!   "span": Span
+//! }
+//! ```
+//!
+//! The values are converted to the JSON booleans, numbers, strings, arrays and objects.
+//! The lines and columns start at 1, and the columns are counted in characters.
+//!
+//! The version is incremented when a field is removed or changes meaning: new fields can be added
+//! without changing the version.
+
+use std::fmt::Write;
+
+use {Command, EnumToStr, ParseResult, Value, keys_to_string};
+use errors::{Diagnostic, Error};
+use position::Span;
+
+/// The version of the JSON schema.
+pub const VERSION: u32 = 1;
+
+/// Convert the errors and warnings of a parse result to JSON, with the commands if `commands` is true.
+pub fn to_json<T: EnumToStr>(result: &ParseResult<T>, commands: bool) -> String {
+    let mut diagnostics: Vec<String> = result.errors.iter()
+        .map(error_to_json)
+        .collect();
+    diagnostics.extend(result.warnings.iter().map(|warning| diagnostic_to_json(warning, None, None, None)));
+    let mut json = format!("{{\"version\":{},\"diagnostics\":[{}]", VERSION, diagnostics.join(","));
+    if commands {
+        let commands: Vec<_> = result.commands.iter()
+            .zip(&result.spans)
+            .map(|(command, span)| command_to_json(command, span))
+            .collect();
+        let _ = write!(json, ",\"commands\":[{}]", commands.join(","));
+    }
+    json.push('}');
+    json
+}
+
+fn command_to_json<T: EnumToStr>(command: &Command<T>, span: &Span) -> String {
+    let named = |kind: &str, name: &str| vec![("command", string(kind)), ("name", string(name))];
+    let mut fields =
+        match *command {
+            Command::App(ref name) => named("app", name),
+            Command::Custom(ref custom) => {
+                let argument = custom.argument().map_or_else(|| "null".to_string(), |argument| string(&argument));
+                let count = custom.count().map_or_else(|| "null".to_string(), |count| count.to_string());
+                let mut fields = named("custom", custom.name());
+                fields.push(("argument", argument));
+                fields.push(("count", count));
+                fields
+            },
+            Command::Flag(ref name) => named("flag", name),
+            Command::Map { ref action, ref keys, ref mode } => vec![
+                ("command", string("map")),
+                ("mode", string(mode)),
+                ("keys", string(&keys_to_string(keys))),
+                ("action", string(action)),
+            ],
+            Command::Query(ref name) => named("query", name),
+            Command::Reset(ref name) => named("reset", name),
+            Command::Set(ref name, ref value) => {
+                let mut fields = named("set", name);
+                fields.push(("value", value_to_json(value)));
+                fields
+            },
+            Command::Toggle(ref name) => named("toggle", name),
+            Command::Unmap { ref keys, ref mode } => vec![
+                ("command", string("unmap")),
+                ("mode", string(mode)),
+                ("keys", string(&keys_to_string(keys))),
+            ],
+            Command::Update(ref name, operator, ref value) => {
+                let mut fields = named("update", name);
+                fields.push(("operator", string(&operator.to_string())));
+                fields.push(("value", value_to_json(value)));
+                fields
+            },
+        };
+    fields.push(("span", span_to_json(span)));
+    object(&fields)
+}
+
+fn diagnostic_to_json(diagnostic: &Diagnostic, unexpected: Option<&str>, expected: Option<&str>,
+                      suggestion: Option<&str>) -> String
+{
+    let optional_string = |value: Option<&str>| value.map_or_else(|| "null".to_string(), string);
+    object(&[
+        ("severity", string(&diagnostic.severity.to_string())),
+        ("code", string(diagnostic.code)),
+        ("message", string(&diagnostic.message)),
+        ("unexpected", optional_string(unexpected)),
+        ("expected", optional_string(expected)),
+        ("suggestion", optional_string(suggestion)),
+        ("span", diagnostic.span.as_ref().map_or_else(|| "null".to_string(), span_to_json)),
+    ])
+}
+
+fn error_to_json(error: &Error) -> String {
+    let diagnostic = Diagnostic::from(error);
+    match *error {
+        Error::Parse(ref error) =>
+            diagnostic_to_json(&diagnostic, Some(&error.unexpected), Some(&error.expected),
+                               error.suggestion.as_deref()),
+        _ => diagnostic_to_json(&diagnostic, None, None, None),
+    }
+}
+
+/// Create a JSON object from its fields, whose values are already in JSON.
+fn object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<_> = fields.iter()
+        .map(|&(name, ref value)| format!("{}:{}", string(name), value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn span_to_json(span: &Span) -> String {
+    let file = span.source.as_ref().map_or_else(|| "null".to_string(), |path| string(&path.to_string_lossy()));
+    format!("{{\"file\":{},\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}},\
+             \"bytes\":{{\"start\":{},\"end\":{}}}}}",
+            file, span.start.line, span.start.column, span.end.line, span.end.column, span.bytes.start,
+            span.bytes.end)
+}
+
+/// Create a JSON string, escaping the quotes, the backslashes and the control characters.
+fn string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", character as u32);
+            },
+            character => json.push(character),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn value_to_json(value: &Value) -> String {
+    match *value {
+        Value::Bool(boolean) => boolean.to_string(),
+        // NOTE: JSON has no representation for the infinities and NaN.
+        Value::Float(float) if !float.is_finite() => "null".to_string(),
+        Value::Float(float) => format!("{:?}", float),
+        Value::Int(int) => int.to_string(),
+        Value::List(ref list) => {
+            let values: Vec<_> = list.iter().map(value_to_json).collect();
+            format!("[{}]", values.join(","))
+        },
+        Value::Map(ref map) => {
+            let fields: Vec<_> = map.iter()
+                .map(|(key, value)| format!("{}:{}", string(key), value_to_json(value)))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        },
+        Value::Str(ref text) => string(text),
+    }
+}
@@ -20,32 +20,199 @@
  */
 
 //! Type for representing keys and functions for parsing strings into `Key`s.
+//!
+//! The printable characters are written as is, except `<`, `>`, `|` and `\` which are written `<lt>`,
+//! `<gt>`, `<Bar>` and `<Bslash>`, and any character can be written with its code, like `<Char-0x20>`
+//! or `<Char-32>` for a space.
+//! The modifiers are written `A-` (Alt), `C-` (Control), `D-` (Super), `H-` (Hyper), `M-` (Meta) and
+//! `S-` (Shift), like `<C-S-Tab>`, and the double and triple clicks of the mouse buttons are written
+//! `2-` and `3-`, like `<C-2-LeftMouse>`.
+//! A key converted to a string is parsed back to its normalized form (see `CanonicalKey`).
 
 use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+use std::result;
+use std::str::FromStr;
 
-use errors::{ParseError, Result};
+use errors::{Error, KeyError, ParseError, Result};
 use errors::ErrorType::Parse;
 use position::Pos;
 
 use self::Key::*;
 
-/// Structure to represent which keys were pressed.
-struct ConstructorKeys {
-    alt: bool,
-    control: bool,
-    shift: bool,
+/// The characters written with a name, with their name.
+const ESCAPES: &[(char, &str)] = &[('<', "lt"), ('>', "gt"), ('|', "Bar"), ('\\', "Bslash")];
+
+/// The set of modifiers pressed with a key.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Modifiers {
+    /// Whether Alt is pressed.
+    pub alt: bool,
+    /// Whether Control is pressed.
+    pub control: bool,
+    /// Whether Hyper is pressed.
+    pub hyper: bool,
+    /// Whether Meta is pressed.
+    pub meta: bool,
+    /// Whether Shift is pressed.
+    pub shift: bool,
+    /// Whether Super (the Windows or Command key) is pressed.
+    pub super_: bool,
+}
+
+impl Modifiers {
+    /// Create an empty set of modifiers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if no modifier is pressed.
+    pub fn is_empty(&self) -> bool {
+        !self.alt && !self.control && !self.hyper && !self.meta && !self.shift && !self.super_
+    }
 }
 
-impl ConstructorKeys {
-    fn new() -> Self {
-        ConstructorKeys {
-            alt: false,
-            control: false,
-            shift: false,
+/// A key in its canonical form: the base key, which is not a modifier, with the set of modifiers.
+///
+/// Equivalent spellings of a key give the same canonical key: the order and repetition of the
+/// modifiers do not matter, and Shift on a letter is folded into the letter, which is uppercased, so
+/// that `<S-a>`, `<S-A>` and `A` are the same key, and so are `<C-S-a>` and `<C-A>`.
+/// Shift is kept on the other keys, like `<S-Tab>`.
+/// The space and tab characters are the `Space` and `Tab` keys, so that `<Char-32>` is `<Space>`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CanonicalKey {
+    key: Key,
+    modifiers: Modifiers,
+}
+
+impl CanonicalKey {
+    /// Create a canonical key from a key and the modifiers pressed with it.
+    /// The modifiers of `key`, if any, are added to `modifiers`.
+    pub fn new(key: Key, mut modifiers: Modifiers) -> Self {
+        let mut key = key;
+        loop {
+            key =
+                match key {
+                    Alt(key) => {
+                        modifiers.alt = true;
+                        *key
+                    },
+                    Control(key) => {
+                        modifiers.control = true;
+                        *key
+                    },
+                    Hyper(key) => {
+                        modifiers.hyper = true;
+                        *key
+                    },
+                    Meta(key) => {
+                        modifiers.meta = true;
+                        *key
+                    },
+                    Shift(key) => {
+                        modifiers.shift = true;
+                        *key
+                    },
+                    Super(key) => {
+                        modifiers.super_ = true;
+                        *key
+                    },
+                    // NOTE: the modifiers of the clicked button are moved outside of the click.
+                    DoubleClick(key) => {
+                        let button = CanonicalKey::new(*key, modifiers);
+                        break CanonicalKey {
+                            key: DoubleClick(Box::new(button.key)),
+                            modifiers: button.modifiers,
+                        };
+                    },
+                    TripleClick(key) => {
+                        let button = CanonicalKey::new(*key, modifiers);
+                        break CanonicalKey {
+                            key: TripleClick(Box::new(button.key)),
+                            modifiers: button.modifiers,
+                        };
+                    },
+                    key => break CanonicalKey::normalize_shift(key, modifiers),
+                };
+        }
+    }
+
+    /// Get the base key, which is not a modifier.
+    /// The base key of a double or triple click is the click of the button, like `DoubleClick(LeftMouse)`.
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    /// Get the modifiers pressed with the key.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Fold Shift into a letter having a single uppercase character, and replace the space and tab
+    /// characters by their keys.
+    fn normalize_shift(key: Key, mut modifiers: Modifiers) -> Self {
+        let key =
+            match key {
+                Char(' ') => Space,
+                Char('\t') => Tab,
+                Char(character) if modifiers.shift && character.is_alphabetic() => {
+                    let mut uppercase = character.to_uppercase();
+                    match (uppercase.next(), uppercase.next()) {
+                        (Some(uppercase), None) if uppercase != character || character.is_uppercase() => {
+                            modifiers.shift = false;
+                            Char(uppercase)
+                        },
+                        _ => Char(character),
+                    }
+                },
+                key => key,
+            };
+        CanonicalKey {
+            key,
+            modifiers,
         }
     }
 }
 
+impl Display for CanonicalKey {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}", Key::from(self.clone()))
+    }
+}
+
+impl From<Key> for CanonicalKey {
+    fn from(key: Key) -> Self {
+        CanonicalKey::new(key, Modifiers::new())
+    }
+}
+
+impl From<CanonicalKey> for Key {
+    /// Create the key with the modifiers in the order used by the parser:
+    /// `Hyper(Super(Meta(Control(Alt(Shift(key))))))`.
+    fn from(key: CanonicalKey) -> Self {
+        let CanonicalKey { mut key, modifiers } = key;
+        if modifiers.shift {
+            key = Shift(Box::new(key));
+        }
+        if modifiers.alt {
+            key = Alt(Box::new(key));
+        }
+        if modifiers.control {
+            key = Control(Box::new(key));
+        }
+        if modifiers.meta {
+            key = Meta(Box::new(key));
+        }
+        if modifiers.super_ {
+            key = Super(Box::new(key));
+        }
+        if modifiers.hyper {
+            key = Hyper(Box::new(key));
+        }
+        key
+    }
+}
+
 /// Enum representing the keys that can be used in a mapping.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Key {
@@ -54,6 +221,7 @@ pub enum Key {
     Char(char),
     Control(Box<Key>),
     Delete,
+    DoubleClick(Box<Key>),
     Down,
     End,
     Enter,
@@ -70,16 +238,68 @@ pub enum Key {
     F10,
     F11,
     F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
     Home,
+    Hyper(Box<Key>),
     Insert,
+    Keypad0,
+    Keypad1,
+    Keypad2,
+    Keypad3,
+    Keypad4,
+    Keypad5,
+    Keypad6,
+    Keypad7,
+    Keypad8,
+    Keypad9,
+    KeypadComma,
+    KeypadDivide,
+    KeypadEnter,
+    KeypadEqual,
+    KeypadMinus,
+    KeypadMultiply,
+    KeypadPlus,
+    KeypadPoint,
     Left,
+    LeftMouse,
+    MediaNext,
+    MediaPlayPause,
+    MediaPrevious,
+    MediaStop,
+    Menu,
+    Meta(Box<Key>),
+    MiddleMouse,
     PageDown,
     PageUp,
+    PrintScreen,
     Right,
+    RightMouse,
+    ScrollWheelDown,
+    ScrollWheelLeft,
+    ScrollWheelRight,
+    ScrollWheelUp,
     Shift(Box<Key>),
     Space,
+    Super(Box<Key>),
     Tab,
+    TripleClick(Box<Key>),
     Up,
+    VolumeDown,
+    VolumeMute,
+    VolumeUp,
+    X1Mouse,
+    X2Mouse,
 }
 
 /// Convert a `Key` a to `String`.
@@ -87,11 +307,12 @@ pub enum Key {
 fn key_to_string(key: &Key) -> String {
     let string =
         match *key {
-            Alt(ref key) => return format!("A-{}", key_to_string(&*key)),
+            Alt(ref key) => return format!("A-{}", key_to_string(key)),
             Backspace => "Backspace",
-            Char(character) => return character.to_string(),
-            Control(ref key) => return format!("C-{}", key_to_string(&*key)),
+            Char(character) => return char_name(character),
+            Control(ref key) => return format!("C-{}", key_to_string(key)),
             Delete => "Delete",
+            DoubleClick(ref key) => return format!("2-{}", key_to_string(key)),
             Down => "Down",
             End => "End",
             Enter => "Enter",
@@ -108,51 +329,197 @@ fn key_to_string(key: &Key) -> String {
             F10 => "F10",
             F11 => "F11",
             F12 => "F12",
+            F13 => "F13",
+            F14 => "F14",
+            F15 => "F15",
+            F16 => "F16",
+            F17 => "F17",
+            F18 => "F18",
+            F19 => "F19",
+            F20 => "F20",
+            F21 => "F21",
+            F22 => "F22",
+            F23 => "F23",
+            F24 => "F24",
             Home => "Home",
+            Hyper(ref key) => return format!("H-{}", key_to_string(key)),
             Insert => "Insert",
+            Keypad0 => "k0",
+            Keypad1 => "k1",
+            Keypad2 => "k2",
+            Keypad3 => "k3",
+            Keypad4 => "k4",
+            Keypad5 => "k5",
+            Keypad6 => "k6",
+            Keypad7 => "k7",
+            Keypad8 => "k8",
+            Keypad9 => "k9",
+            KeypadComma => "kComma",
+            KeypadDivide => "kDivide",
+            KeypadEnter => "kEnter",
+            KeypadEqual => "kEqual",
+            KeypadMinus => "kMinus",
+            KeypadMultiply => "kMultiply",
+            KeypadPlus => "kPlus",
+            KeypadPoint => "kPoint",
             Left => "Left",
+            LeftMouse => "LeftMouse",
+            MediaNext => "MediaNext",
+            MediaPlayPause => "MediaPlayPause",
+            MediaPrevious => "MediaPrevious",
+            MediaStop => "MediaStop",
+            Menu => "Menu",
+            Meta(ref key) => return format!("M-{}", key_to_string(key)),
+            MiddleMouse => "MiddleMouse",
             PageDown => "PageDown",
             PageUp => "PageUp",
+            PrintScreen => "PrintScreen",
             Right => "Right",
-            Shift(ref key) => return format!("S-{}", key_to_string(&*key)),
+            RightMouse => "RightMouse",
+            ScrollWheelDown => "ScrollWheelDown",
+            ScrollWheelLeft => "ScrollWheelLeft",
+            ScrollWheelRight => "ScrollWheelRight",
+            ScrollWheelUp => "ScrollWheelUp",
+            Shift(ref key) => return format!("S-{}", key_to_string(key)),
             Space => "Space",
+            Super(ref key) => return format!("D-{}", key_to_string(key)),
             Tab => "Tab",
+            TripleClick(ref key) => return format!("3-{}", key_to_string(key)),
             Up => "Up",
+            VolumeDown => "VolumeDown",
+            VolumeMute => "VolumeMute",
+            VolumeUp => "VolumeUp",
+            X1Mouse => "X1Mouse",
+            X2Mouse => "X2Mouse",
         };
     string.to_string()
 }
 
+/// Get the name of a character in a special key, like `lt` in `<C-lt>`.
+fn char_name(character: char) -> String {
+    match escape_name(character) {
+        Some(name) => name.to_string(),
+        None if is_plain_char(character) => character.to_string(),
+        None => format!("Char-0x{:x}", character as u32),
+    }
+}
+
+/// Get the name of a character that is written with a name, like `lt` for `<`.
+fn escape_name(character: char) -> Option<&'static str> {
+    ESCAPES.iter()
+        .find(|&&(escaped, _)| escaped == character)
+        .map(|&(_, name)| name)
+}
+
+/// Check if `character` can be written as is in a mapping, i.e. it is printable: neither a control
+/// character nor a whitespace.
+fn is_plain_char(character: char) -> bool {
+    !character.is_control() && !character.is_whitespace()
+}
+
 impl Display for Key {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        let string = key_to_string(self);
-        let string =
-            if let Char(_) = *self {
-                string
-            }
-            else {
-                format!("<{}>", string)
-            };
-        write!(formatter, "{}", string)
+        match *self {
+            Char(character) if is_plain_char(character) && escape_name(character).is_none() =>
+                write!(formatter, "{}", character),
+            _ => write!(formatter, "<{}>", key_to_string(self)),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = KeyError;
+
+    /// Parse a single key, like `a` or `<C-w>`.
+    fn from_str(input: &str) -> result::Result<Self, KeyError> {
+        if input.is_empty() {
+            return Err(KeyError {
+                column: 1,
+                expected: "key".to_string(),
+                unexpected: "<end of input>".to_string(),
+            });
+        }
+        let (key, size) = parse_key(input, 1, 1).map_err(key_error)?;
+        if size < input.len() {
+            return Err(KeyError {
+                column: input[..size].chars().count() + 1,
+                expected: "<end of input>".to_string(),
+                unexpected: input[size..].to_string(),
+            });
+        }
+        Ok(key)
+    }
+}
+
+/// A sequence of keys, like `<C-w>v`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct KeySequence(pub Vec<Key>);
+
+impl Deref for KeySequence {
+    type Target = [Key];
+
+    fn deref(&self) -> &[Key] {
+        &self.0
+    }
+}
+
+impl Display for KeySequence {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        for key in &self.0 {
+            write!(formatter, "{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<Key>> for KeySequence {
+    fn from(keys: Vec<Key>) -> Self {
+        KeySequence(keys)
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = KeyError;
+
+    /// Parse a sequence of keys, like `<C-w>v`.
+    fn from_str(input: &str) -> result::Result<Self, KeyError> {
+        parse_keys(input, 1, 1)
+            .map(KeySequence)
+            .map_err(key_error)
     }
 }
 
-fn key_constructor(key: Key, constructor_keys: &ConstructorKeys) -> Key {
-    let mut ctrl_constructor: fn(Key) -> Key = |key| key;
-    if constructor_keys.control {
-        ctrl_constructor = |key| Control(Box::new(key));
+/// Convert the error of `parse_keys()` to a key error.
+fn key_error(error: Error) -> KeyError {
+    match error {
+        Error::Parse(error) => KeyError {
+            column: error.pos().column,
+            expected: error.expected,
+            unexpected: error.unexpected,
+        },
+        error => KeyError {
+            column: 1,
+            expected: "key".to_string(),
+            unexpected: error.to_string(),
+        },
     }
+}
 
-    let mut shift_constructor: fn(Key) -> Key = |key| key;
-    if constructor_keys.shift {
-        shift_constructor = |key| Shift(Box::new(key));
+impl Key {
+    /// Get the canonical form of this key.
+    pub fn canonical(&self) -> CanonicalKey {
+        CanonicalKey::from(self.clone())
     }
 
-    let mut alt_constructor: fn(Key) -> Key = |key| key;
-    if constructor_keys.alt {
-        alt_constructor = |key| Alt(Box::new(key));
+    /// Get the key written the way the parser creates it, i.e. with the modifiers in the order
+    /// `Hyper(Super(Meta(Control(Alt(Shift(key))))))` and with Shift folded into the letters.
+    pub fn normalize(self) -> Key {
+        Key::from(CanonicalKey::from(self))
     }
+}
 
-    ctrl_constructor(alt_constructor(shift_constructor(key)))
+fn key_constructor(key: Key, modifiers: Modifiers) -> Key {
+    Key::from(CanonicalKey::new(key, modifiers))
 }
 
 fn parse_key(input: &str, line_num: usize, column_num: usize) -> Result<(Key, usize)> {
@@ -170,45 +537,49 @@ fn parse_key(input: &str, line_num: usize, column_num: usize) -> Result<(Key, us
                     ));
 
                 }
-                let mut end = key.clone();
-                if end.len() >= 2 && (&end[..2] == "A-" || &end[..2] == "C-" || &end[..2] == "S-") {
+                let mut end = key.as_str();
+                if is_modifier(end) {
+                    let mut clicks = 1;
                     let mut delta = 0;
-                    let mut constructor_keys = ConstructorKeys::new();
-                    while end.len() >= 2 && (&end[..2] == "A-" || &end[..2] == "C-" || &end[..2] == "S-") {
-                        let new_end = {
-                            let (start, new_end) = end.split_at(2);
-                            match start {
-                                "A-" => constructor_keys.alt = true,
-                                "C-" => constructor_keys.control = true,
-                                "S-" => constructor_keys.shift = true,
-                                _ => unreachable!(),
-                            }
-                            delta += 2;
-                            new_end.to_string()
-                        };
-                        end = new_end;
+                    let mut modifiers = Modifiers::new();
+                    // NOTE: get() gives None when the prefix would split a character.
+                    while let Some(start) = end.get(..2) {
+                        match start {
+                            "2-" => clicks = 2,
+                            "3-" => clicks = 3,
+                            "A-" => modifiers.alt = true,
+                            "C-" => modifiers.control = true,
+                            "D-" => modifiers.super_ = true,
+                            "H-" => modifiers.hyper = true,
+                            "M-" => modifiers.meta = true,
+                            "S-" => modifiers.shift = true,
+                            _ => break,
+                        }
+                        delta += 2;
+                        end = &end[2..];
                     }
 
-                    let character = end.chars().next().unwrap(); // NOTE: There is at least one character, hence unwrap.
-                    let result_special_key = special_key(&end, line_num, column_num + delta, true)
-                        .map(|(key, size)| (key_constructor(key, &constructor_keys), size + delta));
+                    let result_special_key = special_key(end, line_num, column_num + delta, true);
                     match result_special_key {
-                        Ok(result) => result,
+                        Ok((key, size)) => {
+                            let key = with_clicks(key, clicks, end, line_num, column_num + delta)?;
+                            (key_constructor(key, modifiers), size + delta)
+                        },
                         Err(error) => {
-                            match character {
-                                'A' ... 'Z' | 'a' ... 'z' => {
-                                    if end.len() == 1 {
-                                        (key_constructor(Char(character), &constructor_keys), 5)
-                                    }
-                                    else {
-                                        return Err(ParseError::new(
-                                            Parse,
-                                            end.to_string(),
-                                            "one character".to_string(),
-                                            Pos::new(line_num, column_num + 3)
-                                        ));
-                                    }
+                            let mut characters = end.chars();
+                            match (characters.next(), characters.next()) {
+                                (Some(character), None) if is_plain_char(character) => {
+                                    let key = with_clicks(Char(character), clicks, end, line_num, column_num + delta)?;
+                                    // NOTE: the size includes the < and > around the key.
+                                    (key_constructor(key, modifiers), delta + character.len_utf8() + 2)
                                 },
+                                (Some(character), Some(_)) if character.is_alphabetic() =>
+                                    return Err(ParseError::new(
+                                        Parse,
+                                        end.to_string(),
+                                        "one character".to_string(),
+                                        Pos::new(line_num, column_num + 3)
+                                    )),
                                 _ => return Err(error),
                             }
                         },
@@ -219,10 +590,8 @@ fn parse_key(input: &str, line_num: usize, column_num: usize) -> Result<(Key, us
                 }
             },
             Some(character) => {
-                let characters = "=+-;!\"'#%&()*,./<>?@[\\]^_{|}~çÇéÉàÀèÈ$";
                 match character {
-                    'A' ... 'Z' | 'a' ... 'z' => (Char(character), 1),
-                    _ if characters.contains(character) => (Char(character), 1),
+                    _ if is_plain_char(character) => (Char(character), character.len_utf8()),
                     _ =>
                         return Err(ParseError::new(
                             Parse,
@@ -232,20 +601,61 @@ fn parse_key(input: &str, line_num: usize, column_num: usize) -> Result<(Key, us
                         ))
                 }
             },
-            None => unreachable!() ,
+            None =>
+                return Err(ParseError::new(
+                    Parse,
+                    "<end of line>".to_string(),
+                    "key".to_string(),
+                    Pos::new(line_num, column_num)
+                )),
         };
     Ok(result)
 }
 
+/// Check if the `input` starts with a modifier like `C-`, or a number of clicks like `2-`.
+fn is_modifier(input: &str) -> bool {
+    ["2-", "3-", "A-", "C-", "D-", "H-", "M-", "S-"].iter().any(|modifier| input.starts_with(modifier))
+}
+
+/// Check if `key` is a mouse button, which can be double or triple clicked.
+fn is_mouse_button(key: &Key) -> bool {
+    matches!(*key, LeftMouse | MiddleMouse | RightMouse | X1Mouse | X2Mouse)
+}
+
+/// Create the double or triple click of `key` if `clicks` is 2 or 3.
+/// `name` is the name of the key, used in the error when the key is not a mouse button.
+fn with_clicks(key: Key, clicks: usize, name: &str, line_num: usize, column_num: usize) -> Result<Key> {
+    if clicks == 1 {
+        return Ok(key);
+    }
+    if !is_mouse_button(&key) {
+        return Err(ParseError::new(
+            Parse,
+            name.to_string(),
+            "mouse button".to_string(),
+            Pos::new(line_num, column_num + 1)
+        ));
+    }
+    let key =
+        if clicks == 2 {
+            DoubleClick(Box::new(key))
+        }
+        else {
+            TripleClick(Box::new(key))
+        };
+    Ok(key)
+}
+
 /// Parse a string into a vector of `Key`s.
+/// The keys are normalized: see `CanonicalKey` for the rules.
 pub fn parse_keys(mut input: &str, line_num: usize, column_num: usize) -> Result<Vec<Key>> {
     let mut keys = vec![];
     let mut index = 0;
     while !input.is_empty() {
         let (key, size) = parse_key(input, line_num, column_num + index)?;
         keys.push(key);
+        index += input[..size].chars().count();
         input = &input[size..];
-        index += size;
     }
     Ok(keys)
 }
@@ -254,40 +664,110 @@ pub fn parse_keys(mut input: &str, line_num: usize, column_num: usize) -> Result
 fn special_key(key: &str, line_num: usize, column_num: usize, in_special_key: bool) -> Result<(Key, usize)> {
     let expected =
         if in_special_key {
-            "A-Z or special key"
+            "character or special key"
         }
         else {
             "special key"
         };
     let result =
         match key {
-            "Backspace" => (Backspace, 11),
-            "Delete" => (Delete, 8),
-            "Down" => (Down, 6),
-            "End" => (End, 5),
-            "Enter" => (Enter, 7),
-            "Esc" => (Escape, 5),
-            "F1" => (F1, 4),
-            "F2" => (F2, 4),
-            "F3" => (F3, 4),
-            "F4" => (F4, 4),
-            "F5" => (F5, 4),
-            "F6" => (F6, 4),
-            "F7" => (F7, 4),
-            "F8" => (F8, 4),
-            "F9" => (F9, 4),
-            "F10" => (F10, 5),
-            "F11" => (F11, 5),
-            "F12" => (F12, 5),
-            "Home" => (Home, 6),
-            "Insert" => (Insert, 8),
-            "Left" => (Left, 6),
-            "PageDown" => (PageDown, 10),
-            "PageUp" => (PageUp, 8),
-            "Right" => (Right, 7),
-            "Space" => (Space, 7),
-            "Tab" => (Tab, 5),
-            "Up" => (Up, 4),
+            "Backspace" => Backspace,
+            "Bar" => Char('|'),
+            "Bslash" => Char('\\'),
+            "Delete" => Delete,
+            "Down" => Down,
+            "End" => End,
+            "Enter" => Enter,
+            "Esc" => Escape,
+            "F1" => F1,
+            "F2" => F2,
+            "F3" => F3,
+            "F4" => F4,
+            "F5" => F5,
+            "F6" => F6,
+            "F7" => F7,
+            "F8" => F8,
+            "F9" => F9,
+            "F10" => F10,
+            "F11" => F11,
+            "F12" => F12,
+            "F13" => F13,
+            "F14" => F14,
+            "F15" => F15,
+            "F16" => F16,
+            "F17" => F17,
+            "F18" => F18,
+            "F19" => F19,
+            "F20" => F20,
+            "F21" => F21,
+            "F22" => F22,
+            "F23" => F23,
+            "F24" => F24,
+            "gt" => Char('>'),
+            "Home" => Home,
+            "Insert" => Insert,
+            "k0" => Keypad0,
+            "k1" => Keypad1,
+            "k2" => Keypad2,
+            "k3" => Keypad3,
+            "k4" => Keypad4,
+            "k5" => Keypad5,
+            "k6" => Keypad6,
+            "k7" => Keypad7,
+            "k8" => Keypad8,
+            "k9" => Keypad9,
+            "kComma" => KeypadComma,
+            "kDivide" => KeypadDivide,
+            "kEnter" => KeypadEnter,
+            "kEqual" => KeypadEqual,
+            "kMinus" => KeypadMinus,
+            "kMultiply" => KeypadMultiply,
+            "kPlus" => KeypadPlus,
+            "kPoint" => KeypadPoint,
+            "Left" => Left,
+            "LeftMouse" => LeftMouse,
+            "lt" => Char('<'),
+            "MediaNext" => MediaNext,
+            "MediaPlayPause" => MediaPlayPause,
+            "MediaPrevious" => MediaPrevious,
+            "MediaStop" => MediaStop,
+            "Menu" => Menu,
+            "MiddleMouse" => MiddleMouse,
+            "PageDown" => PageDown,
+            "PageUp" => PageUp,
+            "PrintScreen" => PrintScreen,
+            "Right" => Right,
+            "RightMouse" => RightMouse,
+            "ScrollWheelDown" => ScrollWheelDown,
+            "ScrollWheelLeft" => ScrollWheelLeft,
+            "ScrollWheelRight" => ScrollWheelRight,
+            "ScrollWheelUp" => ScrollWheelUp,
+            "Space" => Space,
+            "Tab" => Tab,
+            "Up" => Up,
+            "VolumeDown" => VolumeDown,
+            "VolumeMute" => VolumeMute,
+            "VolumeUp" => VolumeUp,
+            "X1Mouse" => X1Mouse,
+            "X2Mouse" => X2Mouse,
+            _ if key.starts_with("Char-") => {
+                let code = &key[5..];
+                let code =
+                    match code.strip_prefix("0x") {
+                        Some(hex) => u32::from_str_radix(hex, 16),
+                        None => code.parse(),
+                    };
+                match code.ok().and_then(::std::char::from_u32) {
+                    // NOTE: normalized so that `<Char-32>` gives the same key as `<Space>`.
+                    Some(character) => Char(character).normalize(),
+                    None => return Err(ParseError::new(
+                        Parse,
+                        key.to_string(),
+                        "character code".to_string(),
+                        Pos::new(line_num, column_num + 1)
+                    )),
+                }
+            },
             _ => return Err(ParseError::new(
                      Parse,
                      key.to_string(),
@@ -295,5 +775,6 @@ fn special_key(key: &str, line_num: usize, column_num: usize, in_special_key: bo
                      Pos::new(line_num, column_num + 1)
                  )),
         };
-    Ok(result)
+    // NOTE: the size includes the < and > around the key.
+    Ok((result, key.len() + 2))
 }
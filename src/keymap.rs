@@ -0,0 +1,312 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Dispatch of the keys pressed by the user to the actions of the mappings.
+//!
+//! The keys are given one at a time to `KeyMap::feed()`, which tells whether they trigger mappings.
+//! A sequence which is both a mapping and the prefix of other mappings, like `g` with `gg`, is
+//! ambiguous: the application should call `KeyMap::check_timeout()` when `KeyMap::timeout_remaining()`
+//! elapses to trigger the shorter mapping if no other key was pressed.
+//! When a key does not continue the longer mappings, like `j` after `g`, the shorter mapping is
+//! triggered and the keys typed after it are dispatched again.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use Command;
+use key::Key;
+use key::Key::Char;
+
+/// The default time to wait for the next key of an ambiguous or incomplete sequence.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Source of the current time, to be able to control the time in the tests.
+pub trait Clock {
+    /// Get the current time.
+    fn now(&self) -> Instant;
+}
+
+/// Clock giving the system time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The result of giving a key to the key map.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Dispatch {
+    /// The keys form a mapping which is also the prefix of other mappings, like `g` with `gg`.
+    /// The mapping is triggered by `KeyMap::check_timeout()` if no other key is pressed, or by the next
+    /// key if it does not continue the other mappings.
+    Ambiguous,
+    /// The keys form a mapping: its action along with the count typed before, like 5 in `5j`.
+    Matched(String, Option<u32>),
+    /// The keys do not form a mapping: all the keys typed since the last dispatch, including the count.
+    NoMatch(Vec<Key>),
+    /// The keys (or the count) are the prefix of mappings: more keys are needed.
+    Pending,
+}
+
+/// A node of the trie of the mappings of a mode.
+#[derive(Default)]
+struct Node {
+    action: Option<String>,
+    children: HashMap<Key, Node>,
+}
+
+impl Node {
+    /// Remove the action of the mapping `keys`, and the nodes that become useless.
+    /// Returns whether a mapping was removed.
+    fn remove(&mut self, keys: &[Key]) -> bool {
+        match keys.split_first() {
+            None => self.action.take().is_some(),
+            Some((key, rest)) => {
+                let (removed, empty) =
+                    match self.children.get_mut(key) {
+                        Some(child) => (child.remove(rest), child.action.is_none() && child.children.is_empty()),
+                        None => return false,
+                    };
+                if empty {
+                    self.children.remove(key);
+                }
+                removed
+            },
+        }
+    }
+}
+
+/// The mappings of every mode, with the keys typed so far.
+///
+/// The keys are normalized, so that `Shift(Control(Tab))` triggers the mapping `<C-S-Tab>`.
+pub struct KeyMap<C: Clock = SystemClock> {
+    clock: C,
+    count: Option<u32>,
+    last_key_time: Option<Instant>,
+    /// The action of the longest mapping of the pending keys, with the number of pending keys it uses.
+    last_match: Option<(String, usize)>,
+    modes: HashMap<String, Node>,
+    pending_keys: Vec<Key>,
+    pending_mode: String,
+    /// The number of keys of `pending_keys` forming the count.
+    pending_count_len: usize,
+    timeout: Duration,
+}
+
+impl Default for KeyMap<SystemClock> {
+    fn default() -> Self {
+        KeyMap::new()
+    }
+}
+
+impl KeyMap<SystemClock> {
+    /// Create a new key map without mappings.
+    pub fn new() -> Self {
+        KeyMap::new_with_clock(SystemClock)
+    }
+}
+
+impl<C: Clock> KeyMap<C> {
+    /// Create a new key map getting the current time from `clock`.
+    pub fn new_with_clock(clock: C) -> Self {
+        KeyMap {
+            clock,
+            count: None,
+            last_key_time: None,
+            last_match: None,
+            modes: HashMap::new(),
+            pending_keys: vec![],
+            pending_mode: String::new(),
+            pending_count_len: 0,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Add the mapping of a map command or remove the mapping of an unmap command.
+    /// Returns false for the other commands.
+    pub fn apply<T>(&mut self, command: &Command<T>) -> bool {
+        match *command {
+            Command::Map { ref action, ref keys, ref mode } => self.map(mode, keys, action),
+            Command::Unmap { ref keys, ref mode } => {
+                self.unmap(mode, keys);
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// Trigger the longest mapping of the pending keys or give them back when the timeout has elapsed
+    /// since the last key.
+    /// Returns no dispatch if the timeout has not elapsed.
+    pub fn check_timeout(&mut self) -> Vec<Dispatch> {
+        let mut dispatches = vec![];
+        if self.timeout_remaining() == Some(Duration::from_secs(0)) {
+            self.flush(&mut dispatches);
+        }
+        dispatches
+    }
+
+    /// Give a key pressed in `mode` to the key map.
+    /// Typing a key in another mode than the previous keys discards these keys.
+    /// Returns several dispatches when the key triggers a shorter mapping and the keys typed after it
+    /// are dispatched again: the last dispatch tells the state of the key map.
+    pub fn feed(&mut self, mode: &str, key: Key) -> Vec<Dispatch> {
+        if mode != self.pending_mode {
+            self.reset();
+            self.pending_mode = mode.to_string();
+        }
+        let mut dispatches = vec![];
+        self.feed_key(key.normalize(), &mut dispatches);
+        dispatches
+    }
+
+    /// Add a mapping from `keys` to `action` in `mode`, replacing the previous mapping of these keys.
+    pub fn map(&mut self, mode: &str, keys: &[Key], action: &str) {
+        if keys.is_empty() {
+            return;
+        }
+        let mut node = self.modes.entry(mode.to_string()).or_default();
+        for key in keys {
+            node = node.children.entry(key.clone().normalize()).or_default();
+        }
+        node.action = Some(action.to_string());
+        self.reset();
+    }
+
+    /// Discard the keys typed so far.
+    pub fn reset(&mut self) {
+        self.count = None;
+        self.last_key_time = None;
+        self.last_match = None;
+        self.pending_keys.clear();
+        self.pending_count_len = 0;
+    }
+
+    /// Set the time to wait for the next key of an ambiguous or incomplete sequence.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Get the time left before the pending keys time out, or `None` if there is nothing to time out.
+    /// A count alone never times out.
+    pub fn timeout_remaining(&self) -> Option<Duration> {
+        if self.pending_keys.len() == self.pending_count_len {
+            return None;
+        }
+        let elapsed = self.clock.now().saturating_duration_since(self.last_key_time?);
+        Some(self.timeout.checked_sub(elapsed).unwrap_or_else(|| Duration::from_secs(0)))
+    }
+
+    /// Remove the mapping of `keys` in `mode`.
+    /// Returns whether there was such a mapping.
+    pub fn unmap(&mut self, mode: &str, keys: &[Key]) -> bool {
+        self.reset();
+        let keys: Vec<_> = keys.iter()
+            .map(|key| key.clone().normalize())
+            .collect();
+        match self.modes.get_mut(mode) {
+            Some(root) => root.remove(&keys),
+            None => false,
+        }
+    }
+
+    /// Get the value of `key` if it is part of a count: a digit following a count, or a digit other than 0
+    /// that does not start a mapping.
+    fn count_digit(&self, key: &Key) -> Option<u32> {
+        let digit =
+            match *key {
+                Char(character) => character.to_digit(10)?,
+                _ => return None,
+            };
+        if self.count.is_some() {
+            return Some(digit);
+        }
+        let is_mapped = self.modes.get(&self.pending_mode)
+            .is_some_and(|root| root.children.contains_key(key));
+        if digit == 0 || is_mapped {
+            None
+        }
+        else {
+            Some(digit)
+        }
+    }
+
+    /// Give a key to the key map, in the mode of the pending keys.
+    fn feed_key(&mut self, key: Key, dispatches: &mut Vec<Dispatch>) {
+        self.last_key_time = Some(self.clock.now());
+        if self.pending_keys.len() == self.pending_count_len {
+            if let Some(digit) = self.count_digit(&key) {
+                self.count = Some(self.count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                self.pending_keys.push(key);
+                self.pending_count_len += 1;
+                dispatches.push(Dispatch::Pending);
+                return;
+            }
+        }
+        self.pending_keys.push(key);
+        let action =
+            match self.pending_node() {
+                None => return self.flush(dispatches),
+                Some(node) => node.action.clone().map(|action| (action, node.children.is_empty())),
+            };
+        match action {
+            Some((action, true)) => {
+                dispatches.push(Dispatch::Matched(action, self.count));
+                self.reset();
+            },
+            Some((action, false)) => {
+                self.last_match = Some((action, self.pending_keys.len()));
+                dispatches.push(Dispatch::Ambiguous);
+            },
+            None => dispatches.push(Dispatch::Pending),
+        }
+    }
+
+    /// Trigger the longest mapping of the pending keys and dispatch the keys typed after it again, or
+    /// give back the pending keys if they contain no mapping.
+    fn flush(&mut self, dispatches: &mut Vec<Dispatch>) {
+        match self.last_match.take() {
+            Some((action, length)) => {
+                let keys = self.pending_keys.split_off(length);
+                dispatches.push(Dispatch::Matched(action, self.count));
+                self.reset();
+                for key in keys {
+                    self.feed_key(key, dispatches);
+                }
+            },
+            None => {
+                dispatches.push(Dispatch::NoMatch(self.pending_keys.clone()));
+                self.reset();
+            },
+        }
+    }
+
+    /// Get the node of the pending keys, without the count.
+    fn pending_node(&self) -> Option<&Node> {
+        let mut node = self.modes.get(&self.pending_mode)?;
+        for key in &self.pending_keys[self.pending_count_len..] {
+            node = node.children.get(key)?;
+        }
+        Some(node)
+    }
+}
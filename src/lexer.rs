@@ -0,0 +1,457 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Tokenizer for the lines of a config file.
+
+use errors::{ParseError, Result};
+use errors::ErrorType::Parse;
+use position::Pos;
+
+use self::TokenKind::*;
+
+/// The characters that are tokens by themselves.
+const OPERATORS: &str = "=,[]{}";
+
+/// The two-character operators of the set command.
+const COMPOUND_OPERATORS: [&str; 3] = ["+=", "-=", "^="];
+
+/// The kind of a token.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind {
+    /// A comment, from `#` to the end of the line.
+    Comment,
+    /// An identifier or any other bare word.
+    Identifier,
+    /// A key sequence like `<C-o>o`.
+    KeyNotation,
+    /// A bare word starting with a digit, optionally preceded by a sign.
+    Number,
+    /// An operator or a delimiter: `=`, `+=`, `-=`, `^=`, `,`, `[`, `]`, `{` or `}`.
+    Operator,
+    /// The rest of a line, taken verbatim.
+    Rest,
+    /// A quoted string literal, with its escape sequences resolved.
+    Str(String),
+}
+
+/// The location of a token in a line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    /// The column, in characters starting at 1, of the start of the token.
+    pub column: usize,
+    /// The byte index following the end of the token.
+    pub end: usize,
+    /// The column following the end of the token.
+    pub end_column: usize,
+    /// The byte index of the start of the token.
+    pub start: usize,
+}
+
+/// A token along with its text and location.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token<'a> {
+    /// The kind of token.
+    pub kind: TokenKind,
+    /// The location of the token.
+    pub span: Span,
+    /// The source text of the token.
+    pub text: &'a str,
+}
+
+impl<'a> Token<'a> {
+    /// Check if this token is the operator `operator`.
+    pub fn is_operator(&self, operator: &str) -> bool {
+        self.kind == Operator && self.text == operator
+    }
+}
+
+/// Tokenizer for a single line.
+#[derive(Clone)]
+pub struct Lexer<'a> {
+    column: usize,
+    index: usize,
+    input: &'a str,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    /// Create a new lexer for the `input` found on line `line`.
+    pub fn new(input: &'a str, line: usize) -> Self {
+        Lexer {
+            column: 1,
+            index: 0,
+            input,
+            line,
+        }
+    }
+
+    /// Get the compound operator starting at the current position, if any.
+    fn compound_operator(&self) -> Option<&'static str> {
+        let rest = &self.input[self.index..];
+        COMPOUND_OPERATORS.iter()
+            .find(|operator| rest.starts_with(*operator))
+            .cloned()
+    }
+
+    /// Advance by one character.
+    fn advance(&mut self, character: char) {
+        self.index += character.len_utf8();
+        self.column += 1;
+    }
+
+    /// Get the location of the end of the line.
+    pub fn end_span(&self) -> Span {
+        let column = self.column + self.input[self.index..].chars().count();
+        Span {
+            column,
+            end: self.input.len(),
+            end_column: column,
+            start: self.input.len(),
+        }
+    }
+
+    /// Get the next key sequence, which extends to the next whitespace.
+    pub fn key_notation(&mut self) -> Option<Token<'a>> {
+        self.skip_whitespace();
+        let token = self.take_while(KeyNotation, |character| !character.is_whitespace());
+        if token.text.is_empty() {
+            None
+        }
+        else {
+            Some(token)
+        }
+    }
+
+    /// Get the next token, or `None` at the end of the line.
+    pub fn next_token(&mut self) -> Result<Option<Token<'a>>> {
+        self.skip_whitespace();
+        let character =
+            match self.peek_char() {
+                Some(character) => character,
+                None => return Ok(None),
+            };
+        let token =
+            match character {
+                '#' => self.take_while(Comment, |_| true),
+                '"' | '\'' => self.string_literal(character)?,
+                _ if OPERATORS.contains(character) => {
+                    let start = self.position();
+                    self.advance(character);
+                    self.token(Operator, start)
+                },
+                _ if self.compound_operator().is_some() => {
+                    let start = self.position();
+                    for character in self.input[self.index..].chars().take(2) {
+                        self.advance(character);
+                    }
+                    self.token(Operator, start)
+                },
+                _ => {
+                    let mut token = self.word(Identifier);
+                    if is_number_literal(token.text) {
+                        token.kind = Number;
+                    }
+                    token
+                },
+            };
+        Ok(Some(token))
+    }
+
+    /// Get the next token without consuming it.
+    pub fn peek_token(&self) -> Result<Option<Token<'a>>> {
+        self.clone().next_token()
+    }
+
+    /// Get the next character without consuming it.
+    fn peek_char(&self) -> Option<char> {
+        self.input[self.index..].chars().next()
+    }
+
+    /// Get the current byte index and column.
+    fn position(&self) -> (usize, usize) {
+        (self.index, self.column)
+    }
+
+    /// Get the text of the line from the start of `token`, without the trailing whitespace.
+    pub fn text_from(&self, token: &Token) -> &'a str {
+        self.input[token.span.start..].trim_end()
+    }
+
+    /// Get the rest of the line, without the surrounding whitespace, or `None` if it is empty.
+    pub fn rest(&mut self) -> Option<Token<'a>> {
+        self.skip_whitespace();
+        let start = self.position();
+        let rest = self.input[self.index..].trim_end();
+        for character in rest.chars() {
+            self.advance(character);
+        }
+        let token = self.token(Rest, start);
+        self.skip_whitespace();
+        if token.text.is_empty() {
+            None
+        }
+        else {
+            Some(token)
+        }
+    }
+
+    /// Skip the whitespace characters.
+    fn skip_whitespace(&mut self) {
+        while let Some(character) = self.peek_char() {
+            if !character.is_whitespace() {
+                break;
+            }
+            self.advance(character);
+        }
+    }
+
+    /// Parse a string literal starting with the quote `quote`.
+    fn string_literal(&mut self, quote: char) -> Result<Token<'a>> {
+        let start = self.position();
+        self.advance(quote);
+        let mut string = String::new();
+        while let Some(character) = self.peek_char() {
+            if character == quote {
+                self.advance(character);
+                return Ok(self.token(Str(string), start));
+            }
+            if character == '\\' {
+                string.push(self.escape_sequence()?);
+            }
+            else {
+                string.push(character);
+                self.advance(character);
+            }
+        }
+        Err(ParseError::new(
+            Parse,
+            "<end of line>".to_string(),
+            quote.to_string(),
+            Pos::new(self.line, self.column)
+        ))
+    }
+
+    /// Parse an escape sequence starting with a backslash.
+    fn escape_sequence(&mut self) -> Result<char> {
+        let start = self.position();
+        self.advance('\\');
+        let character =
+            match self.peek_char() {
+                Some(character) => character,
+                // NOTE: let the caller report the missing closing quote.
+                None => return Ok('\\'),
+            };
+        self.advance(character);
+        let escaped =
+            match character {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                '\\' | '"' | '\'' => character,
+                'u' => self.unicode_escape(start)?,
+                _ => return Err(ParseError::new(
+                    Parse,
+                    format!("\\{}", character),
+                    "escape sequence".to_string(),
+                    Pos::new(self.line, start.1)
+                )),
+            };
+        Ok(escaped)
+    }
+
+    /// Parse the `{XXXX}` part of a `\u{XXXX}` escape sequence whose backslash is at `start`.
+    fn unicode_escape(&mut self, (start_index, start_column): (usize, usize)) -> Result<char> {
+        let input = self.input;
+        let line = self.line;
+        let error = || ParseError::new(
+            Parse,
+            input[start_index..].chars().take_while(|&character| character != '}').collect(),
+            "unicode escape sequence like \\u{00e9}".to_string(),
+            Pos::new(line, start_column)
+        );
+        if self.peek_char() != Some('{') {
+            return Err(error());
+        }
+        self.advance('{');
+        let mut digits = String::new();
+        while let Some(character) = self.peek_char() {
+            self.advance(character);
+            if character == '}' {
+                return u32::from_str_radix(&digits, 16).ok()
+                    .filter(|_| !digits.is_empty() && digits.len() <= 6)
+                    .and_then(::std::char::from_u32)
+                    .ok_or_else(error);
+            }
+            digits.push(character);
+        }
+        Err(error())
+    }
+
+    /// Consume the characters while `predicate` is true and create a token of kind `kind` with them.
+    fn take_while<F: Fn(char) -> bool>(&mut self, kind: TokenKind, predicate: F) -> Token<'a> {
+        let start = self.position();
+        while let Some(character) = self.peek_char() {
+            if !predicate(character) {
+                break;
+            }
+            self.advance(character);
+        }
+        self.token(kind, start)
+    }
+
+    /// Get the bare word starting at the current position, which ends at a whitespace or an operator.
+    fn word(&mut self, kind: TokenKind) -> Token<'a> {
+        let start = self.position();
+        while let Some(character) = self.peek_char() {
+            if character.is_whitespace() || OPERATORS.contains(character) || self.compound_operator().is_some() {
+                break;
+            }
+            self.advance(character);
+        }
+        self.token(kind, start)
+    }
+
+    /// Create a token of kind `kind` from `start` to the current position.
+    fn token(&self, kind: TokenKind, (start, column): (usize, usize)) -> Token<'a> {
+        Token {
+            kind,
+            span: Span {
+                column,
+                end: self.index,
+                end_column: self.column,
+                start,
+            },
+            text: &self.input[start..self.index],
+        }
+    }
+}
+
+/// Check if `word` has the shape of a number literal, optionally preceded by a sign: a radix prefix like
+/// `0x` followed by alphanumeric characters, or digits with `.`, `_` and an exponent.
+/// The other words starting with a digit, like `800x600` or `5px`, are not numbers.
+fn is_number_literal(word: &str) -> bool {
+    let literal = word.strip_prefix(['+', '-']).unwrap_or(word);
+    if !literal.starts_with(|character: char| character.is_ascii_digit()) {
+        return false;
+    }
+    match literal.get(..2) {
+        Some("0x") | Some("0X") | Some("0o") | Some("0O") | Some("0b") | Some("0B") =>
+            literal[2..].chars().all(|character| character.is_ascii_alphanumeric() || character == '_'),
+        _ => {
+            let mut previous = ' ';
+            literal.chars().all(|character| {
+                let is_valid =
+                    match character {
+                        '0' ..= '9' | '_' | '.' | 'e' | 'E' => true,
+                        // NOTE: a sign is only valid in the exponent.
+                        '+' | '-' => matches!(previous, 'e' | 'E' | '+' | '-'),
+                        _ => false,
+                    };
+                previous = character;
+                is_valid
+            })
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lexer;
+    use super::TokenKind::{self, *};
+
+    fn tokens(input: &str) -> Vec<(TokenKind, &str, usize, usize)> {
+        let mut lexer = Lexer::new(input, 1);
+        let mut tokens = vec![];
+        while let Some(token) = lexer.next_token().unwrap() {
+            tokens.push((token.kind, token.text, token.span.start, token.span.column));
+        }
+        tokens
+    }
+
+    fn texts(input: &str) -> Vec<&str> {
+        tokens(input).into_iter()
+            .map(|(_, text, _, _)| text)
+            .collect()
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(tokens("set a=5"), vec![
+            (Identifier, "set", 0, 1),
+            (Identifier, "a", 4, 5),
+            (Operator, "=", 5, 6),
+            (Number, "5", 6, 7),
+        ]);
+        assert_eq!(tokens("-5 +1 - -a"), vec![
+            (Number, "-5", 0, 1),
+            (Number, "+1", 3, 4),
+            (Identifier, "-", 6, 7),
+            (Identifier, "-a", 8, 9),
+        ]);
+        assert_eq!(texts("set a= 5"), vec!["set", "a", "=", "5"]);
+        assert_eq!(texts("set a =5"), vec!["set", "a", "=", "5"]);
+        assert_eq!(texts("[1,{a}]"), vec!["[", "1", ",", "{", "a", "}", "]"]);
+        assert_eq!(texts("set a+=5 b -= c^=-1"), vec!["set", "a", "+=", "5", "b", "-=", "c", "^=", "-1"]);
+        assert_eq!(texts("set a! b& c? C++ +-"), vec!["set", "a!", "b&", "c?", "C++", "+-"]);
+    }
+
+    #[test]
+    fn test_numbers() {
+        let kinds = |input| tokens(input).into_iter()
+            .map(|(kind, _, _, _)| kind)
+            .collect::<Vec<_>>();
+        assert_eq!(kinds("1.2.3 1e+-3 0xZ 1_ -2E5"), vec![Number, Number, Number, Number, Number]);
+        assert_eq!(kinds("800x600 5px 2fa 2020-01-01 -1- .5"),
+            vec![Identifier, Identifier, Identifier, Identifier, Identifier, Identifier]);
+    }
+
+    #[test]
+    fn test_spans() {
+        assert_eq!(tokens("été = \"à\" # é"), vec![
+            (Identifier, "été", 0, 1),
+            (Operator, "=", 6, 5),
+            (Str("à".to_string()), "\"à\"", 8, 7),
+            (Comment, "# é", 13, 11),
+        ]);
+    }
+
+    #[test]
+    fn test_rest() {
+        let mut lexer = Lexer::new("nmap <C-o>o  :open  tab ", 1);
+        assert_eq!(Some("nmap"), lexer.next_token().unwrap().map(|token| token.text));
+        let keys = lexer.key_notation().unwrap();
+        assert_eq!((KeyNotation, "<C-o>o", 6), (keys.kind, keys.text, keys.span.column));
+        let rest = lexer.rest().unwrap();
+        assert_eq!((Rest, ":open  tab", 14, 24), (rest.kind, rest.text, rest.span.column, rest.span.end_column));
+        assert_eq!(None, lexer.rest());
+        assert_eq!(None, lexer.next_token().unwrap());
+        assert_eq!(25, lexer.end_span().column);
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(tokens(r#""a\tb\\" 'c\'d\u{e9}'"#).into_iter().map(|token| token.0).collect::<Vec<_>>(),
+            vec![Str("a\tb\\".to_string()), Str("c'dé".to_string())]);
+        assert!(Lexer::new("\"abc", 1).next_token().is_err());
+        assert!(Lexer::new("\"\\u{zz}\"", 1).next_token().is_err());
+    }
+}
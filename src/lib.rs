@@ -26,30 +26,36 @@
 //! Call the `parse` function on the input.
 
 /*
- * TODO: prevent mutual includes.
  * TODO: auto-include files.
- * TODO: support set = without spaces around =.
- * TODO: Add array type.
  */
 
 pub mod errors;
 mod file;
+pub mod json;
 pub mod key;
-#[doc(hidden)]
+pub mod keymap;
+mod lexer;
 pub mod position;
 pub mod settings;
 mod string;
 
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
 use std::marker::PhantomData;
+use std::mem;
 use std::path::{Path, PathBuf};
 
-use errors::{Error, ParseError, Result};
-use errors::ErrorType::{MissingArgument, NoCommand, Parse, UnknownCommand};
+use errors::{Diagnostic, Error, ParseError, Result};
+use errors::ErrorType::{IncludeDepth, MissingArgument, NoCommand, Parse, RecursiveInclude, UnknownCommand};
 use key::{Key, parse_keys};
-use position::Pos;
-use string::{StrExt, check_ident, maybe_word, word, words};
+use lexer::{Lexer, Token};
+use lexer::TokenKind::{self, Comment, Identifier, Number};
+use position::{PathSegment, Pos, Span, Spanned, ValuePath};
+use settings::SettingAlias;
+use string::{StrExt, check_ident, suggest};
 
 use Command::*;
 use Value::*;
@@ -57,7 +63,7 @@ use Value::*;
 #[macro_export]
 macro_rules! rtry {
     ($parse_result:expr, $result:expr) => {
-        rtry_no_return!($parse_result, $result, { return $parse_result; });
+        rtry_no_return!($parse_result, $result, { return $parse_result })
     };
 }
 
@@ -86,7 +92,21 @@ impl CompletionValues for bool {
     }
 }
 
-impl CompletionValues for i64 {
+impl CompletionValues for char {
+    fn completion_values() -> Vec<String> {
+        vec![]
+    }
+}
+
+impl<T: CompletionValues> CompletionValues for Option<T> {
+    fn completion_values() -> Vec<String> {
+        let mut values = T::completion_values();
+        values.push("none".to_string());
+        values
+    }
+}
+
+impl CompletionValues for PathBuf {
     fn completion_values() -> Vec<String> {
         vec![]
     }
@@ -98,6 +118,24 @@ impl CompletionValues for String {
     }
 }
 
+impl<T: CompletionValues> CompletionValues for Vec<T> {
+    fn completion_values() -> Vec<String> {
+        T::completion_values()
+    }
+}
+
+impl<T: CompletionValues, S> CompletionValues for HashMap<String, T, S> {
+    fn completion_values() -> Vec<String> {
+        T::completion_values()
+    }
+}
+
+impl<T: CompletionValues> CompletionValues for BTreeMap<String, T> {
+    fn completion_values() -> Vec<String> {
+        T::completion_values()
+    }
+}
+
 /// The `EnumFromStr` trait is used to specify how to construct an enum value from a string.
 pub trait EnumFromStr
     where Self: Sized
@@ -107,6 +145,23 @@ pub trait EnumFromStr
 
     /// Check wether the enum variant has an argument.
     fn has_argument(variant: &str) -> std::result::Result<bool, String>;
+
+    /// Get the names of the variants, used to suggest a command when an unknown one is entered.
+    fn names() -> Vec<String> {
+        vec![]
+    }
+}
+
+/// The `EnumToStr` trait is used to get back the strings an enum value was constructed from.
+pub trait EnumToStr {
+    /// Get the name of the variant, as given to `EnumFromStr::create`.
+    fn name(&self) -> &'static str;
+
+    /// Get the argument of the variant, or `None` if it has no argument.
+    fn argument(&self) -> Option<String>;
+
+    /// Get the count of the variant, or `None` if it has no count.
+    fn count(&self) -> Option<u32>;
 }
 
 /// Tre `EnumMetaData` trait is used to get associated meta-data for the enum variants.
@@ -134,12 +189,20 @@ pub struct MetaData {
     pub is_special_command: bool,
 }
 
-/// The commands and errors from parsing a config file.
+/// The commands, errors and warnings from parsing a config file.
 pub struct ParseResult<T> {
     /// The parsed commands.
     pub commands: Vec<Command<T>>,
     /// The errors resulting from the parsing.
     pub errors: Vec<Error>,
+    /// The location of each command: `spans[i]` is the location of `commands[i]`.
+    pub spans: Vec<Span>,
+    /// The location of the elements and keys of the value of each command: `value_spans[i]` is for the
+    /// value of `commands[i]`, and is empty when it has no value or the value is not a list or a map.
+    /// The location of a key of a map is the location of the key itself.
+    pub value_spans: Vec<Vec<Spanned<ValuePath>>>,
+    /// The non-fatal issues (warnings and hints) found while parsing.
+    pub warnings: Vec<Diagnostic>,
 }
 
 impl<T> ParseResult<T> {
@@ -149,19 +212,171 @@ impl<T> ParseResult<T> {
         ParseResult {
             commands: vec![],
             errors: vec![],
+            spans: vec![],
+            value_spans: vec![],
+            warnings: vec![],
         }
     }
 
-    fn new_with_command(command: Command<T>) -> Self {
-        ParseResult {
-            commands: vec![command],
-            errors: vec![],
-        }
+    /// Get all the diagnostics: the errors followed by the warnings and hints.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.errors.iter()
+            .map(Diagnostic::from)
+            .chain(self.warnings.iter().cloned())
+            .collect()
     }
 
     fn merge(&mut self, mut parse_result: ParseResult<T>) {
         self.commands.append(&mut parse_result.commands);
         self.errors.append(&mut parse_result.errors);
+        self.spans.append(&mut parse_result.spans);
+        self.value_spans.append(&mut parse_result.value_spans);
+        self.warnings.append(&mut parse_result.warnings);
+    }
+}
+
+/// Trait to convert a setting field back to a `Value` and to describe its type.
+pub trait SettingValue {
+    /// Convert the field to a value.
+    fn to_value(&self) -> Value;
+
+    /// Get the type of the values of this type.
+    fn value_type() -> ValueType;
+}
+
+impl SettingValue for bool {
+    fn to_value(&self) -> Value {
+        Bool(*self)
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Bool
+    }
+}
+
+impl SettingValue for char {
+    fn to_value(&self) -> Value {
+        Str(self.to_string())
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Char
+    }
+}
+
+/// Implement `CompletionValues` and `SettingValue` for integer types, saturating the values that do not
+/// fit in an `i64`.
+macro_rules! integer_setting_value {
+    ($($typ:ty),*) => {
+        $(
+            impl CompletionValues for $typ {
+                fn completion_values() -> Vec<String> {
+                    vec![]
+                }
+            }
+
+            impl SettingValue for $typ {
+                fn to_value(&self) -> Value {
+                    let value = i64::try_from(*self)
+                        .unwrap_or(if *self > <$typ>::default() { i64::MAX } else { i64::MIN });
+                    Int(value)
+                }
+
+                fn value_type() -> ValueType {
+                    ValueType::Int
+                }
+            }
+        )*
+    };
+}
+
+integer_setting_value!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Implement `CompletionValues` and `SettingValue` for floating-point types.
+macro_rules! float_setting_value {
+    ($($typ:ty),*) => {
+        $(
+            impl CompletionValues for $typ {
+                fn completion_values() -> Vec<String> {
+                    vec![]
+                }
+            }
+
+            impl SettingValue for $typ {
+                fn to_value(&self) -> Value {
+                    Float(f64::from(*self))
+                }
+
+                fn value_type() -> ValueType {
+                    ValueType::Float
+                }
+            }
+        )*
+    };
+}
+
+float_setting_value!(f32, f64);
+
+impl<T: SettingValue> SettingValue for Option<T> {
+    fn to_value(&self) -> Value {
+        match *self {
+            Some(ref value) => value.to_value(),
+            None => Str("none".to_string()),
+        }
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Option(Box::new(T::value_type()))
+    }
+}
+
+impl SettingValue for PathBuf {
+    fn to_value(&self) -> Value {
+        Str(self.to_string_lossy().into_owned())
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Path
+    }
+}
+
+impl SettingValue for String {
+    fn to_value(&self) -> Value {
+        Str(self.clone())
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Str
+    }
+}
+
+impl<T: SettingValue> SettingValue for Vec<T> {
+    fn to_value(&self) -> Value {
+        List(self.iter().map(SettingValue::to_value).collect())
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::List(Box::new(T::value_type()))
+    }
+}
+
+impl<T: SettingValue, S> SettingValue for HashMap<String, T, S> {
+    fn to_value(&self) -> Value {
+        Value::Map(self.iter().map(|(key, value)| (key.clone(), value.to_value())).collect())
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Map(Box::new(T::value_type()))
+    }
+}
+
+impl<T: SettingValue> SettingValue for BTreeMap<String, T> {
+    fn to_value(&self) -> Value {
+        Value::Map(self.iter().map(|(key, value)| (key.clone(), value.to_value())).collect())
+    }
+
+    fn value_type() -> ValueType {
+        ValueType::Map(Box::new(T::value_type()))
     }
 }
 
@@ -187,8 +402,18 @@ pub enum Command<T> {
         /// The mode in which this mapping is available.
         mode: String,
     },
+    /// A set command without a value, like `set option` or `set nooption`, which turns a bool option on
+    /// or off (see `Settings::flag_variant()`).
+    Flag(String),
+    /// A set command with a `?` suffix, like `set option?`, which queries the value of an option.
+    Query(String),
+    /// A set command with a `&` suffix, like `set option&`, or an unset command, like `unset option`,
+    /// which resets an option to its default value.
+    Reset(String),
     /// A set command sets a value to an option.
     Set(String, Value),
+    /// A set command with a `!` suffix, like `set option!`, which toggles a bool option.
+    Toggle(String),
     /// An unmap command removes a key mapping.
     Unmap {
         /// The key shortcut to remove.
@@ -196,23 +421,108 @@ pub enum Command<T> {
         /// The mode in which this mapping is available.
         mode: String,
     },
+    /// A set command with an operator, like `set option += value`, which updates an option relatively to
+    /// its current value (see `Settings::update_variant()`).
+    Update(String, SetOperator, Value),
 }
 
+/// The operator of a set command updating the current value of an option.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SetOperator {
+    /// The `+=` operator: add to a number, append to a string or a list, or insert in a map.
+    Add,
+    /// The `^=` operator: multiply a number, prepend to a string or a list, or insert in a map.
+    Prepend,
+    /// The `-=` operator: subtract from a number, or remove from a string, a list or a map.
+    Subtract,
+}
+
+impl SetOperator {
+    /// Get the operator from its textual representation.
+    fn from_operator(operator: &str) -> Option<Self> {
+        match operator {
+            "+=" => Some(SetOperator::Add),
+            "^=" => Some(SetOperator::Prepend),
+            "-=" => Some(SetOperator::Subtract),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SetOperator {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let operator =
+            match *self {
+                SetOperator::Add => "+=",
+                SetOperator::Prepend => "^=",
+                SetOperator::Subtract => "-=",
+            };
+        write!(formatter, "{}", operator)
+    }
+}
+
+/// The default maximum number of nested include commands.
+pub const DEFAULT_MAX_INCLUDE_DEPTH: usize = 32;
+
 /// The parsing configuration.
-#[derive(Default)]
 pub struct Config {
     /// The application library commands.
     pub application_commands: Vec<&'static str>,
     /// The available mapping modes for the map command.
     pub mapping_modes: Vec<&'static str>,
+    /// The maximum number of nested include commands.
+    pub max_include_depth: usize,
+    /// The other names of the settings, usually from `Settings::aliases()`.
+    /// The set commands using them are converted to use the current name, with a warning for the
+    /// deprecated names.
+    pub setting_aliases: Vec<SettingAlias>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            application_commands: vec![],
+            mapping_modes: vec![],
+            max_include_depth: DEFAULT_MAX_INCLUDE_DEPTH,
+            setting_aliases: vec![],
+        }
+    }
+}
+
+/// A file being parsed, given to `Parser::parse_file` or found by an include command.
+struct IncludedFile {
+    /// The canonical path of the file, used to detect include cycles.
+    canonical_path: PathBuf,
+    /// Whether the file was found by an include command, to compute the include depth.
+    included: bool,
+    /// The path of the file, as given to `Parser::parse_file` or found by the include command.
+    path: PathBuf,
+}
+
+/// What was done in the file being parsed, to warn about the lines overriding previous ones.
+#[derive(Default)]
+struct FileState {
+    /// The line where each mapping (mode and keys) was defined.
+    mappings: HashMap<(String, Vec<Key>), usize>,
+    /// The line where each setting was set.
+    settings: HashMap<String, usize>,
 }
 
 /// The config parser.
 pub struct Parser<T> {
-    column: usize,
     config: Config,
+    files: Vec<FileState>,
     include_path: PathBuf,
+    include_stack: Vec<IncludedFile>,
     line: usize,
+    /// The byte index of the start of the current line in the file.
+    line_offset: usize,
+    /// The text of the current line.
+    line_text: String,
+    /// The mappings (mode and keys) defined by everything parsed so far.
+    mapped_keys: HashSet<(String, Vec<Key>)>,
+    /// The path of the file being parsed, if any.
+    source: Option<PathBuf>,
     _phantom: PhantomData<T>,
 }
 
@@ -221,10 +531,15 @@ impl<T: EnumFromStr> Parser<T> {
     /// Create a new parser without config.
     pub fn new() -> Self {
         Parser {
-            column: 1,
             config: Config::default(),
+            files: vec![],
             include_path: Path::new("./").to_path_buf(),
+            include_stack: vec![],
             line: 1,
+            line_offset: 0,
+            line_text: String::new(),
+            mapped_keys: HashSet::new(),
+            source: None,
             _phantom: PhantomData,
         }
     }
@@ -232,153 +547,264 @@ impl<T: EnumFromStr> Parser<T> {
     /// Create a new parser with config.
     pub fn new_with_config(config: Config) -> Self {
         Parser {
-            column: 1,
             config: config,
+            files: vec![],
             include_path: Path::new("./").to_path_buf(),
+            include_stack: vec![],
             line: 1,
+            line_offset: 0,
+            line_text: String::new(),
+            mapped_keys: HashSet::new(),
+            source: None,
             _phantom: PhantomData,
         }
     }
 
-    /// Check that we reached the end of the line.
-    fn check_eol(&self, line: &str, index: usize) -> Result<()> {
-        if line.len() > index {
-            let rest = &line[index..];
-            if let Some(word) = maybe_word(rest) {
-                let index = word.index;
-                return Err(ParseError::new(
-                    Parse,
-                    rest.to_string(),
-                    "<end of line>".to_string(),
-                    Pos::new(self.line, self.column + index),
-                ));
-            }
+    /// Check that including the file at `path` would not exceed the maximum include depth.
+    fn check_include_depth(&self, path: &Path, pos: &Pos) -> Result<()> {
+        let depth = self.include_stack.iter()
+            .filter(|file| file.included)
+            .count();
+        if depth >= self.config.max_include_depth {
+            return Err(ParseError::new(
+                IncludeDepth,
+                format!("include of {}", path.display()),
+                format!("at most {} nested includes", self.config.max_include_depth),
+                pos.clone(),
+            ));
         }
         Ok(())
     }
 
+    /// Check that the file at `path` is not already being parsed.
+    fn check_include_cycle(&self, path: &Path, pos: &Pos) -> Result<IncludedFile> {
+        let canonical_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if self.include_stack.iter().any(|file| file.canonical_path == canonical_path) {
+            let mut chain: Vec<_> = self.include_stack.iter()
+                .map(|file| file.path.clone())
+                .collect();
+            chain.push(path.to_path_buf());
+            return Err(ParseError::new(
+                RecursiveInclude(chain),
+                path.display().to_string(),
+                "file not already included".to_string(),
+                pos.clone(),
+            ));
+        }
+        Ok(IncludedFile {
+            canonical_path,
+            included: true,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Warn about a command overriding a previous one in the same file, or unmapping a key that was
+    /// never mapped.
+    fn check_overrides(&mut self, command: &Command<T>, input: &str, warnings: &mut Vec<Diagnostic>) {
+        let line = self.line;
+        // NOTE: the warnings point to the first argument of the command.
+        let mut lexer = Lexer::new(input, line);
+        let _ = lexer.next_token();
+        let pos = lexer.key_notation()
+            .map_or_else(|| self.span(1, 1), |token| self.span(token.span.column, token.span.end_column));
+        let file =
+            match self.files.last_mut() {
+                Some(file) => file,
+                None => return,
+            };
+        match *command {
+            Set(ref name, _) => {
+                if let Some(previous_line) = file.settings.insert(name.clone(), line) {
+                    warnings.push(Diagnostic::warning("duplicate-setting",
+                        format!("setting {} is already set on line {}", name, previous_line), pos));
+                }
+            },
+            Reset(ref name) => {
+                file.settings.remove(name);
+            },
+            Command::Map { ref keys, ref mode, .. } => {
+                let mapping = (mode.clone(), keys.clone());
+                if let Some(previous_line) = file.mappings.insert(mapping.clone(), line) {
+                    warnings.push(Diagnostic::warning("overridden-mapping",
+                        format!("mapping {} in mode {} overrides the mapping on line {}", keys_to_string(keys), mode,
+                                previous_line), pos));
+                }
+                self.mapped_keys.insert(mapping);
+            },
+            Unmap { ref keys, ref mode } => {
+                let mapping = (mode.clone(), keys.clone());
+                file.mappings.remove(&mapping);
+                // NOTE: this is only a hint since the key could be mapped by the application.
+                if !self.mapped_keys.remove(&mapping) {
+                    warnings.push(Diagnostic::hint("unmapped-key",
+                        format!("{} is not mapped in mode {}", keys_to_string(keys), mode), pos));
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Check that we reached the end of the line.
+    fn check_eol(&self, lexer: &mut Lexer) -> Result<()> {
+        match lexer.next_token()? {
+            None | Some(Token { kind: Comment, .. }) => Ok(()),
+            Some(token) => Err(ParseError::new(
+                Parse,
+                lexer.text_from(&token).to_string(),
+                "<end of line>".to_string(),
+                Pos::new(self.line, token.span.column),
+            )),
+        }
+    }
+
     /// Parse a custom command or return an error if it does not exist.
-    fn custom_command(&self, line: &str, word: &str, start_index: usize, index: usize, prefix: Option<u32>)
-        -> Result<Command<T>>
-    {
+    fn custom_command(&self, lexer: &mut Lexer, word: &Token, prefix: Option<u32>) -> Result<Command<T>> {
+        let name = word.text;
         let args =
-            if line.len() > start_index {
-                line[start_index..].trim()
-            }
-            else if let Ok(true) = T::has_argument(word) {
-                return Err(self.missing_args(start_index));
-            }
-            else {
-                ""
+            match lexer.rest() {
+                Some(args) => args.text,
+                None => {
+                    if let Ok(true) = T::has_argument(name) {
+                        return Err(self.missing_args(word.span.end_column));
+                    }
+                    ""
+                },
             };
-        if let Ok(command) = T::create(word, args, prefix) {
+        if let Ok(command) = T::create(name, args, prefix) {
             Ok(Custom(command))
         }
-        else if self.config.application_commands.contains(&word) {
-            Ok(App(word.to_string()))
+        else if self.config.application_commands.contains(&name) {
+            Ok(App(name.to_string()))
         }
         else {
-            return Err(ParseError::new(
+            Err(ParseError::new_with_suggestion(
                 UnknownCommand,
-                word.to_string(),
+                name.to_string(),
                 "command or comment".to_string(),
-                Pos::new(self.line, index + 1)
+                Pos::new(self.line, word.span.column),
+                self.suggest_command(name),
             ))
         }
     }
 
-    /// Get the rest of the line, starting at `column`, returning an error if the column is greater
-    /// than the line's length.
-    fn get_rest<'a>(&self, line: &'a str, column: usize) -> Result<&'a str> {
-        if line.len() > column {
-            Ok(&line[column..])
-        }
-        else {
-            Err(self.missing_args(column))
+    /// Get the known command closest to `name`, if any.
+    fn suggest_command(&self, name: &str) -> Option<String> {
+        let mut names: Vec<String> = vec!["include".to_string(), "set".to_string(), "unset".to_string()];
+        for mode in &self.config.mapping_modes {
+            names.push(format!("{}map", mode));
+            names.push(format!("{}unmap", mode));
         }
+        names.extend(self.config.application_commands.iter().map(ToString::to_string));
+        names.extend(T::names());
+        suggest(name, names)
     }
 
     /// Parse a line.
     fn line(&mut self, line: &str, prefix: Option<u32>) -> ParseResult<T> {
         let mut result = ParseResult::new();
-        if let Some(word) = maybe_word(line) {
-            let index = word.index;
-            let word = word.word;
-            let start_index = index + word.len() + 1;
-            self.column = start_index + 1;
-
-            let (start3, end3) = word.rsplit_at(3);
-            let (start5, end5) = word.rsplit_at(5);
-            if word.starts_with('#') {
-                return result;
-            }
+        let mut lexer = Lexer::new(line, self.line);
+        let word =
+            match rtry!(result, lexer.next_token()) {
+                Some(Token { kind: Comment, .. }) | None => return result,
+                Some(word) => word,
+            };
+        let (start3, end3) = word.text.rsplit_at(3);
+        let (start5, end5) = word.text.rsplit_at(5);
 
-            if word == "include" {
-                let rest = rtry!(result, self.get_rest(line, start_index));
-                self.include_command(rest)
-            }
-            else {
-                let command =
-                    if word == "set" {
-                        let rest = rtry!(result, self.get_rest(line, start_index));
-                        self.set_command(rest)
-                    }
-                    else if end3 == "map" && self.config.mapping_modes.contains(&start3) {
-                        let rest = rtry!(result, self.get_rest(line, start_index));
-                        self.map_command(rest, start3)
-                    }
-                    else if end5 == "unmap" && self.config.mapping_modes.contains(&start5) {
-                        let rest = rtry!(result, self.get_rest(line, start_index));
-                        self.unmap_command(rest, start5)
-                    }
-                    else {
-                        self.custom_command(line, word, start_index, index, prefix)
-                    };
-                let command = rtry!(result, command);
-                ParseResult::new_with_command(command)
-            }
+        if word.text == "include" {
+            self.include_command(&mut lexer, &word)
         }
         else {
+            let mut value_spans = vec![];
+            let command =
+                if word.text == "set" {
+                    self.set_command(&mut lexer, &word, &mut value_spans, &mut result.warnings)
+                }
+                else if word.text == "unset" {
+                    self.unset_command(&mut lexer, &word, &mut result.warnings)
+                }
+                else if end3 == "map" && self.config.mapping_modes.contains(&start3) {
+                    self.map_command(&mut lexer, &word, start3)
+                }
+                else if end5 == "unmap" && self.config.mapping_modes.contains(&start5) {
+                    self.unmap_command(&mut lexer, &word, start5)
+                }
+                else {
+                    self.custom_command(&mut lexer, &word, prefix)
+                };
+            let command = rtry!(result, command);
+            self.check_overrides(&command, line, &mut result.warnings);
+            // NOTE: the trailing comment is not part of the command, except for the commands taking the rest
+            // of the line as argument.
+            let mut end = line.trim_end();
+            if word.text == "set" || word.text == "unset" {
+                let mut lexer = Lexer::new(line, self.line);
+                while let Ok(Some(token)) = lexer.next_token() {
+                    if token.kind == Comment {
+                        end = line[..token.span.start].trim_end();
+                    }
+                }
+            }
+            let end_column = end.chars().count() + 1;
+            result.commands.push(command);
+            result.spans.push(self.span(word.span.column, end_column));
+            result.value_spans.push(value_spans);
             result
         }
     }
 
     /// Parse an include command.
-    fn include_command(&mut self, line: &str) -> ParseResult<T> {
-        let word = word(line);
-        let index = word.index;
-        let word = word.word;
-        let after_index = index + word.len() + 1;
-        self.column += after_index;
+    fn include_command(&mut self, lexer: &mut Lexer, word: &Token) -> ParseResult<T> {
         let mut result = ParseResult::new();
-        rtry_no_return!(result, self.check_eol(line, after_index), {});
-        let path = Path::new(&self.include_path).join(word);
+        let token = rtry!(result, self.argument(lexer, word));
+        let pos = Pos::new(self.line, token.span.column);
+        rtry_no_return!(result, self.check_eol(lexer), {});
+        let path =
+            match token.kind {
+                TokenKind::Str(ref path) => Path::new(&self.include_path).join(path),
+                _ => Path::new(&self.include_path).join(token.text),
+            };
+        rtry!(result, self.check_include_depth(&path, &pos));
         let file = rtry!(result, file::open(&path));
+        let included_file = rtry!(result, self.check_include_cycle(&path, &pos));
         let buf_reader = BufReader::new(file);
+        self.include_stack.push(included_file);
+        let source = self.source.replace(path);
+        let line_text = mem::take(&mut self.line_text);
+        let (line, line_offset) = (self.line, self.line_offset);
         result.merge(self.parse(buf_reader, None));
+        self.source = source;
+        self.line_text = line_text;
+        self.line = line;
+        self.line_offset = line_offset;
+        self.include_stack.pop();
         result
     }
 
+    /// Get the first argument of the command `word`, returning an error if there is none.
+    fn argument<'a>(&self, lexer: &mut Lexer<'a>, word: &Token) -> Result<Token<'a>> {
+        match lexer.next_token()? {
+            None | Some(Token { kind: Comment, .. }) => Err(self.missing_args(word.span.end_column)),
+            Some(token) => Ok(token),
+        }
+    }
+
     /// Parse a map command.
-    fn map_command(&self, line: &str, mode: &str) -> Result<Command<T>> {
-        let word = word(line);
-        let index = word.index;
-        let word = word.word;
-        let rest = &line[index + word.len() ..].trim();
-        if !rest.is_empty() {
-            Ok(Map {
-                action: rest.to_string(),
-                keys: parse_keys(word, self.line, self.column + index)?,
+    fn map_command(&self, lexer: &mut Lexer, word: &Token, mode: &str) -> Result<Command<T>> {
+        let keys = lexer.key_notation()
+            .ok_or_else(|| self.missing_args(word.span.end_column))?;
+        match lexer.rest() {
+            Some(action) => Ok(Command::Map {
+                action: action.text.to_string(),
+                keys: parse_keys(keys.text, self.line, keys.span.column)?,
                 mode: mode.to_string(),
-            })
-        }
-        else {
-            Err(ParseError::new(
+            }),
+            None => Err(ParseError::new(
                 Parse,
                 "<end of line>".to_string(),
                 "mapping action".to_string(),
-                Pos::new(self.line, self.column + line.len())
-            ))
+                Pos::new(self.line, lexer.end_span().column)
+            )),
         }
     }
 
@@ -393,16 +819,99 @@ impl<T: EnumFromStr> Parser<T> {
     }
 
     /// Parse settings.
-    pub fn parse<R: BufRead>(&mut self, input: R, prefix: Option<u32>) -> ParseResult<T> {
+    pub fn parse<R: BufRead>(&mut self, mut input: R, prefix: Option<u32>) -> ParseResult<T> {
         let mut result = ParseResult::new();
-        for (line_num, input_line) in input.lines().enumerate() {
-            self.line = line_num + 1;
-            let input_line = rtry_no_return!(result, input_line, { continue });
-            result.merge(self.line(&input_line, prefix));
+        self.files.push(FileState::default());
+        let mut buffer = vec![];
+        let mut line_num = 0;
+        let mut offset = 0;
+        loop {
+            buffer.clear();
+            // NOTE: the lines are read as bytes to know their exact size, including the newline.
+            let size =
+                match input.read_until(b'\n', &mut buffer) {
+                    Ok(0) => break,
+                    Ok(size) => size,
+                    Err(error) => {
+                        // NOTE: other errors are likely to happen again on the next read, so stop parsing.
+                        result.errors.push(Error::Io {
+                            path: self.source.clone(),
+                            source: error,
+                        });
+                        break;
+                    },
+                };
+            line_num += 1;
+            self.line = line_num;
+            self.line_offset = offset;
+            offset += size;
+            if buffer.ends_with(b"\n") {
+                buffer.pop();
+                if buffer.ends_with(b"\r") {
+                    buffer.pop();
+                }
+            }
+            match String::from_utf8(mem::take(&mut buffer)) {
+                Ok(input_line) => {
+                    self.line_text = input_line.clone();
+                    let mut line_result = self.line(&input_line, prefix);
+                    self.fill_error_spans(&mut line_result.errors);
+                    result.merge(line_result);
+                },
+                Err(error) => {
+                    // NOTE: an invalid UTF-8 line is skipped.
+                    let utf8_error = error.utf8_error();
+                    buffer = error.into_bytes();
+                    result.errors.push(Error::Io {
+                        path: self.source.clone(),
+                        source: io::Error::new(io::ErrorKind::InvalidData, utf8_error),
+                    });
+                },
+            }
         }
+        self.files.pop();
+        result
+    }
+
+    /// Parse the settings of the file at `path`.
+    /// The spans of the commands and errors of this file refer to `path`.
+    pub fn parse_file<P: AsRef<Path>>(&mut self, path: P, prefix: Option<u32>) -> ParseResult<T> {
+        let path = path.as_ref();
+        let mut result = ParseResult::new();
+        let file = rtry!(result, file::open(path));
+        // The file is on the include stack so that including it again is reported as a cycle.
+        self.include_stack.push(IncludedFile {
+            canonical_path: fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+            included: false,
+            path: path.to_path_buf(),
+        });
+        let source = self.source.replace(path.to_path_buf());
+        let result = self.parse(BufReader::new(file), prefix);
+        self.source = source;
+        self.include_stack.pop();
         result
     }
 
+    /// Set the location of the parse errors that do not have one, assuming they are on the current line.
+    fn fill_error_spans(&self, errors: &mut [Error]) {
+        for error in errors {
+            if let Error::Parse(ref mut error) = *error {
+                if error.span.is_none() {
+                    let column = error.pos().column;
+                    let text_at_column: String = self.line_text.chars().skip(column.saturating_sub(1)).collect();
+                    let end_column =
+                        if !error.unexpected.is_empty() && text_at_column.starts_with(&error.unexpected) {
+                            column + error.unexpected.chars().count()
+                        }
+                        else {
+                            column
+                        };
+                    error.span = Some(Box::new(self.span(column, end_column)));
+                }
+            }
+        }
+    }
+
     /// Parse a single line of settings.
     pub fn parse_line(&mut self, line: &str, prefix: Option<u32>) -> ParseResult<T> {
         let mut result = self.parse(line.as_bytes(), prefix);
@@ -413,41 +922,96 @@ impl<T: EnumFromStr> Parser<T> {
                 "command".to_string(),
                 Pos::new(self.line, 1)
             ));
+            self.fill_error_spans(&mut result.errors);
         }
         result
     }
 
+    /// Get the location of the text of the current line between `start_column` and `end_column`.
+    fn span(&self, start_column: usize, end_column: usize) -> Span {
+        let byte_index = |column: usize| {
+            self.line_text.char_indices()
+                .nth(column.saturating_sub(1))
+                .map_or(self.line_text.len(), |(index, _)| index)
+        };
+        Span {
+            bytes: self.line_offset + byte_index(start_column)..self.line_offset + byte_index(end_column),
+            end: Pos::new(self.line, end_column),
+            source: self.source.clone(),
+            start: Pos::new(self.line, start_column),
+        }
+    }
+
+    /// Replace the other name of the setting of `command` by its current name, with a warning when this
+    /// name is deprecated.
+    fn resolve_alias(&self, mut command: Command<T>, span: Span, warnings: &mut Vec<Diagnostic>) -> Command<T> {
+        {
+            let (name, is_flag) =
+                match command {
+                    Flag(ref mut name) => (name, true),
+                    Query(ref mut name) | Reset(ref mut name) | Set(ref mut name, _) | Toggle(ref mut name) |
+                        Update(ref mut name, _, _) => (name, false),
+                    _ => return command,
+                };
+            let find_alias = |name: &str| self.config.setting_aliases.iter().find(|alias| alias.alias == name);
+            // The `no` prefix of `set nooption` is kept in front of the current name.
+            let found_alias =
+                match find_alias(name) {
+                    Some(alias) => Some(("", alias)),
+                    None if is_flag && name.starts_with("no") => find_alias(&name[2..]).map(|alias| ("no", alias)),
+                    None => None,
+                };
+            if let Some((prefix, alias)) = found_alias {
+                if alias.deprecated {
+                    let mut message = format!("setting {} is deprecated, use {} instead", alias.alias, alias.name);
+                    if let Some(ref note) = alias.note {
+                        message.push_str(": ");
+                        message.push_str(note);
+                    }
+                    warnings.push(Diagnostic::warning("deprecated-setting", message, span));
+                }
+                *name = format!("{}{}", prefix, alias.name);
+            }
+        }
+        command
+    }
+
     /// Parse a set command.
-    fn set_command(&mut self, line: &str) -> Result<Command<T>> {
-        if let Some(words) = words(line, 2) {
-            let index = words[0].index;
-            let word =  words[0].word;
-            let identifier = check_ident(word.to_string(), &Pos::new(self.line, self.column + index))?;
-
-            let operator = words[1].word;
-            let operator_index = words[1].index;
-            if operator == "=" {
-                let rest = &line[operator_index + 1..];
-                self.column += operator_index + 1;
-                Ok(Set(identifier.to_string(), self.value(rest)?))
+    fn set_command(&self, lexer: &mut Lexer, word: &Token, value_spans: &mut Vec<Spanned<ValuePath>>,
+                   warnings: &mut Vec<Diagnostic>) -> Result<Command<T>>
+    {
+        let name = self.argument(lexer, word)?;
+        let (text, suffix) =
+            match name.text.chars().last() {
+                Some(suffix @ '!') | Some(suffix @ '&') | Some(suffix @ '?') =>
+                    (&name.text[..name.text.len() - 1], Some(suffix)),
+                _ => (name.text, None),
+            };
+        let pos = Pos::new(self.line, name.span.column);
+        let identifier = check_ident(text.to_string(), &pos)?;
+        let command =
+            if let Some(suffix) = suffix {
+                self.check_eol(lexer)?;
+                match suffix {
+                    '!' => Toggle(identifier),
+                    '&' => Reset(identifier),
+                    _ => Query(identifier),
+                }
             }
             else {
-                return Err(ParseError::new(
-                    Parse,
-                    operator.to_string(),
-                    "=".to_string(),
-                    Pos::new(self.line, self.column + operator_index)
-                ))
-            }
-        }
-        else {
-            return Err(ParseError::new(
-                Parse,
-                "<end of line>".to_string(),
-                "=".to_string(),
-                Pos::new(self.line, self.column + line.len()),
-            ))
-        }
+                match lexer.next_token()? {
+                    None | Some(Token { kind: Comment, .. }) => Flag(identifier),
+                    Some(ref operator) if operator.is_operator("=") =>
+                        Set(identifier, self.value(lexer, value_spans)?),
+                    Some(token) =>
+                        match SetOperator::from_operator(token.text) {
+                            Some(operator) => Update(identifier, operator, self.value(lexer, value_spans)?),
+                            None => return Err(self.unexpected(lexer, Some(token), "=")),
+                        },
+                }
+            };
+        let span = self.span(name.span.column, name.span.column + text.chars().count());
+        Ok(self.resolve_alias(command, span, warnings))
     }
 
     /// Set the directory where the include command will look for files to include.
@@ -455,48 +1019,248 @@ impl<T: EnumFromStr> Parser<T> {
         self.include_path = directory.as_ref().to_path_buf();
     }
 
+    /// Get an error for the unexpected `token`, or for the end of line if there is no token.
+    fn unexpected(&self, lexer: &Lexer, token: Option<Token>, expected: &str) -> Error {
+        let (unexpected, column) =
+            match token {
+                None | Some(Token { kind: Comment, .. }) => ("<end of line>".to_string(), lexer.end_span().column),
+                Some(token) => (token.text.to_string(), token.span.column),
+            };
+        ParseError::new(
+            Parse,
+            unexpected,
+            expected.to_string(),
+            Pos::new(self.line, column)
+        )
+    }
+
     /// Parse an unmap command.
-    fn unmap_command(&mut self, line: &str, mode: &str) -> Result<Command<T>> {
-        let word = word(line);
-        let index = word.index;
-        let word = word.word;
-        let after_index = index + word.len() + 1;
-        self.column += after_index;
-        self.check_eol(line, after_index)?;
+    fn unmap_command(&self, lexer: &mut Lexer, word: &Token, mode: &str) -> Result<Command<T>> {
+        let keys = lexer.key_notation()
+            .ok_or_else(|| self.missing_args(word.span.end_column))?;
+        self.check_eol(lexer)?;
         Ok(Unmap {
-            keys: parse_keys(word, self.line, self.column + index)?,
+            keys: parse_keys(keys.text, self.line, keys.span.column)?,
             mode: mode.to_string(),
         })
     }
 
-    /// Parse a value.
-    fn value(&self, input: &str) -> Result<Value> {
-        let string: String = input.chars().take_while(|&character| character != '#').collect();
-        let string = string.trim();
-        match string {
-            "" => Err(ParseError::new(
-                      Parse,
-                      "<end of line>".to_string(),
-                      "value".to_string(),
-                      Pos::new(self.line, self.column + string.len())
-                  )),
-            "true" => Ok(Bool(true)),
-            "false" => Ok(Bool(false)),
-            _ => {
-                if string.chars().all(|character| character.is_digit(10)) {
-                    // NOTE: the string only contains digit, hence unwrap.
-                    Ok(Int(string.parse().unwrap()))
+    /// Parse an unset command.
+    fn unset_command(&self, lexer: &mut Lexer, word: &Token, warnings: &mut Vec<Diagnostic>) -> Result<Command<T>> {
+        let name = self.argument(lexer, word)?;
+        let pos = Pos::new(self.line, name.span.column);
+        let identifier = check_ident(name.text.to_string(), &pos)?;
+        self.check_eol(lexer)?;
+        let span = self.span(name.span.column, name.span.end_column);
+        Ok(self.resolve_alias(Reset(identifier), span, warnings))
+    }
+
+    /// Parse a value, adding the location of its elements and keys to `spans`.
+    /// A list, a map or a string literal must be followed by the end of the line, while multiple
+    /// bare words form a single string value.
+    fn value(&self, lexer: &mut Lexer, spans: &mut Vec<Spanned<ValuePath>>) -> Result<Value> {
+        let first = lexer.peek_token()?;
+        match first {
+            Some(ref token) if token.is_operator("[") || token.is_operator("{") || matches!(token.kind, TokenKind::Str(_)) => {
+                let value = self.element(lexer, &mut vec![], spans)?;
+                self.check_eol(lexer)?;
+                Ok(value)
+            },
+            Some(ref token) if token.kind == Identifier || token.kind == Number => {
+                let start = token.clone();
+                let mut end = token.clone();
+                while let Some(token) = lexer.next_token()? {
+                    if token.kind == Comment {
+                        break;
+                    }
+                    end = token;
                 }
-                else if string.chars().all(|character| character.is_digit(10) || character == '.') {
-                    // NOTE: the string only contains digit or dot, hence unwrap.
-                    Ok(Float(string.parse().unwrap()))
+                if start.span.start == end.span.start {
+                    self.scalar(&start)
                 }
                 else {
-                    Ok(Str(input.trim().to_string()))
+                    let text = lexer.text_from(&start);
+                    Ok(Str(text[..end.span.end - start.span.start].to_string()))
                 }
             },
+            token => Err(self.unexpected(lexer, token, "value")),
+        }
+    }
+
+    /// Parse a list value, whose path is `path`.
+    fn list(&self, lexer: &mut Lexer, path: &mut ValuePath, spans: &mut Vec<Spanned<ValuePath>>) -> Result<Value> {
+        let mut values = vec![];
+        loop {
+            match lexer.peek_token()? {
+                Some(ref token) if token.is_operator("]") => {
+                    lexer.next_token()?;
+                    return Ok(List(values));
+                },
+                token => {
+                    path.push(PathSegment::Index(values.len()));
+                    if let Some(token) = token {
+                        spans.push(Spanned::new(path.clone(), self.span(token.span.column, token.span.end_column)));
+                    }
+                    let value = self.element(lexer, path, spans);
+                    path.pop();
+                    values.push(value?);
+                },
+            }
+            match lexer.next_token()? {
+                Some(ref token) if token.is_operator(",") => (),
+                Some(ref token) if token.is_operator("]") => return Ok(List(values)),
+                token => return Err(self.unexpected(lexer, token, ", or ]")),
+            }
+        }
+    }
+
+    /// Parse a map value, whose path is `path`.
+    fn map(&self, lexer: &mut Lexer, path: &mut ValuePath, spans: &mut Vec<Spanned<ValuePath>>) -> Result<Value> {
+        let mut values = BTreeMap::new();
+        loop {
+            let key =
+                match lexer.next_token()? {
+                    Some(ref token) if token.is_operator("}") => return Ok(Value::Map(values)),
+                    Some(Token { kind: TokenKind::Str(key), span, .. }) => (key, span),
+                    Some(Token { kind: Identifier, span, text }) | Some(Token { kind: Number, span, text }) =>
+                        (text.to_string(), span),
+                    token => return Err(self.unexpected(lexer, token, "key")),
+                };
+            match lexer.next_token()? {
+                Some(ref token) if token.is_operator("=") => (),
+                token => return Err(self.unexpected(lexer, token, "=")),
+            }
+            let (key, span) = key;
+            path.push(PathSegment::Key(key.clone()));
+            spans.push(Spanned::new(path.clone(), self.span(span.column, span.end_column)));
+            let value = self.element(lexer, path, spans);
+            path.pop();
+            let value = value?;
+            if values.contains_key(&key) {
+                return Err(ParseError::new(
+                    Parse,
+                    key,
+                    "unique key".to_string(),
+                    Pos::new(self.line, span.column)
+                ));
+            }
+            values.insert(key, value);
+            match lexer.next_token()? {
+                Some(ref token) if token.is_operator(",") => (),
+                Some(ref token) if token.is_operator("}") => return Ok(Value::Map(values)),
+                token => return Err(self.unexpected(lexer, token, ", or }")),
+            }
+        }
+    }
+
+    /// Parse a list or map element, whose path is `path`.
+    fn element(&self, lexer: &mut Lexer, path: &mut ValuePath, spans: &mut Vec<Spanned<ValuePath>>) -> Result<Value> {
+        match lexer.next_token()? {
+            Some(ref token) if token.is_operator("[") => self.list(lexer, path, spans),
+            Some(ref token) if token.is_operator("{") => self.map(lexer, path, spans),
+            Some(Token { kind: TokenKind::Str(string), .. }) => Ok(Str(string)),
+            Some(ref token) if token.kind == Identifier || token.kind == Number => self.scalar(token),
+            token => Err(self.unexpected(lexer, token, "value")),
+        }
+    }
+
+    /// Parse a number literal.
+    /// Integer literals can be written in decimal, or in hexadecimal, octal or binary with the `0x`,
+    /// `0o` and `0b` prefixes. A decimal literal with a fractional part or an exponent is a float.
+    /// Digits can be separated by underscores and the literal can start with a sign.
+    fn number(&self, token: &Token) -> Result<Value> {
+        let error = |expected: String| ParseError::new(
+            Parse,
+            token.text.to_string(),
+            expected,
+            Pos::new(self.line, token.span.column)
+        );
+        let integer_error = || error(format!("integer between {} and {}", i64::MIN, i64::MAX));
+
+        let (sign, literal) =
+            if token.text.starts_with('-') || token.text.starts_with('+') {
+                token.text.split_at(1)
+            }
+            else {
+                ("", token.text)
+            };
+        let radix =
+            match literal.get(..2) {
+                Some("0x") | Some("0X") => 16,
+                Some("0o") | Some("0O") => 8,
+                Some("0b") | Some("0B") => 2,
+                _ => 10,
+            };
+
+        if radix != 10 {
+            let digits = &literal[2..];
+            if !is_digits(digits, radix) {
+                return Err(error("number".to_string()));
+            }
+            let digits = format!("{}{}", sign, digits.replace('_', ""));
+            return i64::from_str_radix(&digits, radix)
+                .map(Int)
+                .map_err(|_| integer_error());
+        }
+
+        let (mantissa, exponent) =
+            match literal.find(['e', 'E']) {
+                Some(index) => (&literal[..index], Some(&literal[index + 1..])),
+                None => (literal, None),
+            };
+        let (integer_part, fractional_part) =
+            match mantissa.find('.') {
+                Some(index) => (&mantissa[..index], Some(&mantissa[index + 1..])),
+                None => (mantissa, None),
+            };
+        let exponent_digits = exponent.map(|exponent| exponent.strip_prefix(['-', '+']).unwrap_or(exponent));
+        let is_valid = is_digits(integer_part, 10)
+            && fractional_part.map(|digits| is_digits(digits, 10)).unwrap_or(true)
+            && exponent_digits.map(|digits| is_digits(digits, 10)).unwrap_or(true);
+        if !is_valid {
+            return Err(error("number".to_string()));
+        }
+
+        let literal = token.text.replace('_', "");
+        if fractional_part.is_none() && exponent.is_none() {
+            literal.parse()
+                .map(Int)
+                .map_err(|_| integer_error())
+        }
+        else {
+            match literal.parse::<f64>() {
+                Ok(float) if float.is_finite() => Ok(Float(float)),
+                Ok(_) => Err(error("finite float".to_string())),
+                Err(_) => Err(error("number".to_string())),
+            }
         }
     }
+
+    /// Convert a bare word to a value.
+    fn scalar(&self, token: &Token) -> Result<Value> {
+        if token.kind == Number {
+            return self.number(token);
+        }
+        match token.text {
+            "true" => Ok(Bool(true)),
+            "false" => Ok(Bool(false)),
+            text => Ok(Str(text.to_string())),
+        }
+    }
+}
+
+/// Convert `keys` back to the key notation, like `<C-w>v`.
+fn keys_to_string(keys: &[Key]) -> String {
+    keys.iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Check if `string` is a non-empty sequence of digits in base `radix`, optionally separated by underscores.
+fn is_digits(string: &str, radix: u32) -> bool {
+    let is_digit = |character: char| character.is_digit(radix);
+    string.starts_with(is_digit) && string.ends_with(is_digit)
+        && string.chars().all(|character| is_digit(character) || character == '_')
 }
 
 /// Trait for converting an identifier like "/" to a special command.
@@ -516,14 +1280,19 @@ pub trait SpecialCommand
 }
 
 /// The `Value` enum represents a value along with its type.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     /// Boolean value.
     Bool(bool),
-    /// Floating-point value.
+    /// Floating-point value, from a literal with a fractional part or an exponent, like `1.5` or `1e-3`.
     Float(f64),
-    /// Integer value.
+    /// Integer value, from a literal without a fractional part nor an exponent, like `-5` or `0xff`.
+    /// It is accepted where a float is expected.
     Int(i64),
+    /// List value.
+    List(Vec<Value>),
+    /// Map value, from string keys to values.
+    Map(BTreeMap<String, Value>),
     /// String value.
     Str(String),
 }
@@ -535,7 +1304,51 @@ impl Value {
             Bool(_) => "bool",
             Float(_) => "float",
             Int(_) => "int",
+            List(_) => "list",
+            Value::Map(_) => "map",
             Str(_) => "string",
         }
     }
 }
+
+/// The `ValueType` enum describes the type of the values of a setting.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueType {
+    /// Boolean type.
+    Bool,
+    /// Character type, from a string of a single character.
+    Char,
+    /// String type restricted to a list of choices, like an enum deriving `Setting`.
+    Choice(Vec<String>),
+    /// Floating-point type, which also accepts integer values.
+    Float,
+    /// Integer type.
+    Int,
+    /// List type with the type of its elements.
+    List(Box<ValueType>),
+    /// Map type, from string keys to values of the specified type.
+    Map(Box<ValueType>),
+    /// Optional type, which can be set to `none`.
+    Option(Box<ValueType>),
+    /// Path type, from a string where a leading `~` is expanded to the home directory.
+    Path,
+    /// String type.
+    Str,
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ValueType::Bool => write!(formatter, "bool"),
+            ValueType::Char => write!(formatter, "character"),
+            ValueType::Choice(ref choices) => write!(formatter, "one of: {}", choices.join(", ")),
+            ValueType::Float => write!(formatter, "float"),
+            ValueType::Int => write!(formatter, "integer"),
+            ValueType::List(ref typ) => write!(formatter, "list of {}", typ),
+            ValueType::Map(ref typ) => write!(formatter, "map of {}", typ),
+            ValueType::Option(ref typ) => write!(formatter, "{} or none", typ),
+            ValueType::Path => write!(formatter, "path"),
+            ValueType::Str => write!(formatter, "string"),
+        }
+    }
+}
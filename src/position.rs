@@ -19,15 +19,23 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
+//! Positions and spans in the config files.
+
 use std::fmt::{Display, Error, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
 
+/// A position in a file.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Pos {
+    /// The column, in characters starting at 1.
     pub column: usize,
+    /// The line, starting at 1.
     pub line: usize,
 }
 
 impl Pos {
+    /// Create a new position.
     pub fn new(line: usize, column: usize) -> Pos {
         Pos {
             column: column,
@@ -35,6 +43,7 @@ impl Pos {
         }
     }
 
+    /// Move the position to the start of the next line.
     pub fn newline(&mut self) {
         self.line += 1;
         self.column = 1;
@@ -47,6 +56,66 @@ impl Display for Pos {
     }
 }
 
+/// The location of a piece of text in a config file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    /// The range of bytes of the text in the file.
+    pub bytes: Range<usize>,
+    /// The position following the end of the text.
+    pub end: Pos,
+    /// The path of the file, or `None` when the text does not come from a file.
+    pub source: Option<PathBuf>,
+    /// The position of the start of the text.
+    pub start: Pos,
+}
+
+impl Display for Span {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+        match self.source {
+            Some(ref source) => write!(formatter, "{}:{}:{}", source.display(), self.start.line, self.start.column),
+            None => write!(formatter, "{}", self.start),
+        }
+    }
+}
+
+/// A step to reach a part of a value: an index of a list or a key of a map.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathSegment {
+    /// The index of an element of a list, starting at 0.
+    Index(usize),
+    /// The key of a map.
+    Key(String),
+}
+
+/// The path of a part of a value, like `[Key("zoom"), Index(1)]` for `2` in `{zoom = [1, 2]}`.
+pub type ValuePath = Vec<PathSegment>;
+
+/// A value along with its location in a config file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spanned<T> {
+    /// The value.
+    pub node: T,
+    /// The location of the value.
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    /// Attach the location `span` to `node`.
+    pub fn new(node: T, span: Span) -> Spanned<T> {
+        Spanned {
+            node,
+            span,
+        }
+    }
+}
+
+impl<T: Display> Display for Spanned<T> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), Error> {
+        write!(formatter, "{}", self.node)
+    }
+}
+
+#[doc(hidden)]
 #[derive(Debug)]
 pub struct WithPos<T> {
     pub node: T,
@@ -21,17 +21,181 @@
 
 //! Settings manager.
 
-use errors::Result;
-use super::Value;
+use std::env;
+use std::path::PathBuf;
+
+use errors::{Error, Result};
+use errors::SettingError::{self, InvalidElement, InvalidKey, UnknownSetting};
+use position::{PathSegment, Span, Spanned, ValuePath};
+use super::{Command, ParseResult, SetOperator, Value, ValueType};
+
+/// Another name of a setting, from the `#[alias = "old-name"]` and
+/// `#[settings(renamed_from = "old-name", note = "...")]` attributes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingAlias {
+    /// The other name of the setting.
+    pub alias: String,
+    /// Whether using the other name should produce a warning.
+    pub deprecated: bool,
+    /// The current name of the setting.
+    pub name: String,
+    /// An explanation attached to the deprecation warning.
+    pub note: Option<String>,
+}
+
+/// Prefix the setting name of an unknown setting error coming from the nested settings `prefix`.
+#[doc(hidden)]
+pub fn nested_error(prefix: &str, error: Error) -> Error {
+    match error {
+        Error::Setting(UnknownSetting { name, suggestion }) => Error::Setting(UnknownSetting {
+            name: format!("{}.{}", prefix, name),
+            suggestion: suggestion.map(|suggestion| format!("{}.{}", prefix, suggestion)),
+        }),
+        error => error,
+    }
+}
+
+/// Get an unknown setting error for `name`, suggesting the closest name among the settings `S`.
+#[doc(hidden)]
+pub fn unknown_setting<S: Settings>(name: &str) -> Error {
+    Error::Setting(SettingError::unknown_setting(name, S::setting_names()))
+}
+
+/// Expand a leading `~` in `path` to the home directory, when it is known.
+pub fn expand_tilde(path: &str) -> PathBuf {
+    if path == "~" || path.starts_with("~/") {
+        if let Some(home) = env::var_os("HOME") {
+            let mut expanded = PathBuf::from(home);
+            if path.len() > 2 {
+                expanded.push(&path[2..]);
+            }
+            return expanded;
+        }
+    }
+    PathBuf::from(path)
+}
 
 /// Settings manager.
 pub trait Settings {
     /// The variant enum representing the setting setters.
     type Variant: Clone;
 
+    /// Get the other names of the settings.
+    /// Give them to the parser with `Config::setting_aliases` to get warnings when the deprecated names
+    /// are used.
+    fn aliases() -> Vec<SettingAlias>;
+
+    /// Apply the setting commands of a parse result, in order, ignoring the other commands.
+    /// The errors are returned along with the location of the command causing them, or the location
+    /// of the invalid element or key for the errors in lists and maps.
+    fn apply<T>(&mut self, result: &ParseResult<T>) -> Vec<Spanned<Error>>
+        where Self: Default
+    {
+        result.commands.iter()
+            .zip(&result.spans)
+            .zip(&result.value_spans)
+            .filter_map(|((command, span), value_spans)| self.apply_command(command).err()
+                .map(|error| {
+                    let span = value_span(&error, value_spans).unwrap_or_else(|| span.clone());
+                    Spanned::new(error, span)
+                }))
+            .collect()
+    }
+
+    /// Apply a setting command, doing nothing for the other commands and for the `set option?` command.
+    fn apply_command<T>(&mut self, command: &Command<T>) -> Result<()>
+        where Self: Default
+    {
+        let variant =
+            match *command {
+                Command::Flag(ref name) => Self::flag_variant(name)?,
+                Command::Reset(ref name) => return self.reset(name),
+                Command::Set(ref name, ref value) => Self::to_variant(name, value.clone())?,
+                Command::Toggle(ref name) => self.toggle_variant(name)?,
+                Command::Update(ref name, operator, ref value) => self.update_variant(name, operator, value.clone())?,
+                _ => return Ok(()),
+            };
+        self.set_value(variant);
+        Ok(())
+    }
+
+    /// Get the default value of a setting as a variant, for the `set option&` command.
+    fn default_variant(name: &str) -> Result<Self::Variant>
+        where Self: Default
+    {
+        Self::default().get_variant(name)
+    }
+
+    /// Convert a setting name from the `set option` or `set nooption` commands to a variant turning a
+    /// bool setting on or off.
+    fn flag_variant(name: &str) -> Result<Self::Variant>;
+
+    /// Reset a setting to its default value, for the `set option&` and `unset option` commands.
+    fn reset(&mut self, name: &str) -> Result<()>
+        where Self: Default
+    {
+        let variant = Self::default_variant(name)?;
+        self.set_value(variant);
+        Ok(())
+    }
+
+    /// Reset all the settings to their default value.
+    fn reset_all(&mut self)
+        where Self: Default
+    {
+        *self = Self::default();
+    }
+
+    /// Get the current value of a setting.
+    fn get_value(&self, name: &str) -> Result<Value>;
+
+    /// Get the current value of a setting as a variant.
+    fn get_variant(&self, name: &str) -> Result<Self::Variant>;
+
     /// Set a setting value from its variant.
     fn set_value(&mut self, value: Self::Variant);
 
+    /// Get the names of all the settings, in declaration order, with the dotted names of the nested
+    /// settings, like `hints.chars`.
+    fn setting_names() -> Vec<String>;
+
+    /// Get the type of a setting.
+    fn setting_type(name: &str) -> Result<ValueType>;
+
     /// Convert a name and value to a variant.
     fn to_variant(name: &str, value: Value) -> Result<Self::Variant>;
+
+    /// Get a variant toggling the current value of a bool setting, for the `set option!` command.
+    fn toggle_variant(&self, name: &str) -> Result<Self::Variant>;
+
+    /// Get a variant applying `operator` with `value` to the current value of a setting, for the
+    /// `set option += value` commands.
+    fn update_variant(&self, name: &str, operator: SetOperator, value: Value) -> Result<Self::Variant>;
+}
+
+/// Get the location of the deepest element or key of a value leading to `error`.
+fn value_span(error: &Error, value_spans: &[Spanned<ValuePath>]) -> Option<Span> {
+    let mut error =
+        match *error {
+            Error::Setting(ref error) => error,
+            _ => return None,
+        };
+    let mut path = vec![];
+    loop {
+        match *error {
+            InvalidElement { index, error: ref element_error } => {
+                path.push(PathSegment::Index(index));
+                error = element_error;
+            },
+            InvalidKey { ref key, error: ref key_error } => {
+                path.push(PathSegment::Key(key.clone()));
+                error = key_error;
+            },
+            _ => break,
+        }
+    }
+    value_spans.iter()
+        .filter(|value_span| path.starts_with(&value_span.node))
+        .max_by_key(|value_span| value_span.node.len())
+        .map(|value_span| value_span.span.clone())
 }
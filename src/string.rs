@@ -38,7 +38,7 @@ impl<'a> StrExt<'a> for &'a str {
     }
 
     fn rsplit_at(&'a self, index: usize) -> (&'a str, &'a str) {
-        if self.len() > index {
+        if self.len() > index && self.is_char_boundary(self.len() - index) {
             self.split_at(self.len() - index)
         }
         else {
@@ -47,141 +47,101 @@ impl<'a> StrExt<'a> for &'a str {
     }
 }
 
-/// A word found in a string with its index.
-#[derive(Debug, PartialEq)]
-pub struct Word<'a> {
-    pub index: usize,
-    pub word: &'a str,
-}
-
-/// Check if a string is an identifier.
+/// Check if a string is an identifier, or a dotted identifier like `hints.chars`.
 pub fn check_ident(string: String, pos: &Pos) -> Result<String> {
-    if string.chars().all(|character| character.is_alphanumeric() || character == '-' || character == '_') {
-        if let Some(true) = string.chars().next().map(|character| character.is_alphabetic()) {
-            return Ok(string)
-        }
-    }
-    Err(ParseError::new(Parse, string, "identifier".to_string(), pos.clone()))
-}
-
-/// Parse a single word.
-pub fn maybe_word(input: &str) -> Option<Word> {
-    let word = word(input);
-    if word.word.is_empty() {
-        None
+    let is_ident = string.split('.').all(|part| {
+        part.chars().all(|character| character.is_alphanumeric() || character == '-' || character == '_') &&
+            part.chars().next().map(|character| character.is_alphabetic()).unwrap_or(false)
+    });
+    if is_ident {
+        Ok(string)
     }
     else {
-        Some(word)
+        Err(ParseError::new(Parse, string, "identifier".to_string(), pos.clone()))
     }
 }
 
-/// Parse a single word.
-/// This function assumes there is always at least a word in `input`.
-pub fn word(input: &str) -> Word {
-    let start = input.chars().take_while(|c| c.is_whitespace()).count();
-    let end = input[start..].find(char::is_whitespace)
-        .map(|index| index + start)
-        .unwrap_or_else(|| input.len());
-    Word {
-        index: start,
-        word: &input[start..end],
+/// Get the number of character insertions, deletions, substitutions and transpositions of adjacent
+/// characters needed to turn `string1` into `string2`.
+pub fn edit_distance(string1: &str, string2: &str) -> usize {
+    let chars1: Vec<char> = string1.chars().collect();
+    let chars2: Vec<char> = string2.chars().collect();
+    // NOTE: distances[i][j] is the distance between the first i characters of string1 and the first j
+    // characters of string2.
+    let mut distances = vec![vec![0; chars2.len() + 1]; chars1.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
     }
-}
-
-/// Parse `count` words.
-pub fn words(input: &str, count: usize) -> Option<Vec<Word>> {
-    let mut vec = vec![];
-    let mut start_index = 0;
-    for (i, character) in input.chars().enumerate() {
-        if character.is_whitespace() {
-            let word = &input[start_index..i];
-            if !word.is_empty() {
-                vec.push(Word {
-                    index: start_index,
-                    word,
-                });
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..chars1.len() + 1 {
+        for j in 1..chars2.len() + 1 {
+            let cost = if chars1[i - 1] == chars2[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j - 1] + cost)
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && chars1[i - 1] == chars2[j - 2] && chars1[i - 2] == chars2[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
             }
-            start_index = i + 1;
+            distances[i][j] = distance;
         }
     }
-    let len = input.len();
-    if start_index < len {
-        vec.push(Word {
-            index: start_index,
-            word: &input[start_index..len],
-        });
-    }
+    distances[chars1.len()][chars2.len()]
+}
 
-    vec.truncate(count);
-    if vec.len() == count {
-        Some(vec)
-    }
-    else {
-        None
+/// Get the candidate closest to `name`, if it is close enough to be a likely typo.
+pub fn suggest<I>(name: &str, candidates: I) -> Option<String>
+    where I: IntoIterator,
+          I::Item: AsRef<str>,
+{
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut best: Option<(usize, String)> = None;
+    for candidate in candidates {
+        let candidate = candidate.as_ref();
+        let length_difference = (candidate.chars().count() as isize - name.chars().count() as isize).abs();
+        if length_difference as usize > max_distance {
+            continue;
+        }
+        let distance = edit_distance(name, candidate);
+        if distance == 0 || distance > max_distance {
+            continue;
+        }
+        if let Some((best_distance, _)) = best {
+            if distance >= best_distance {
+                continue;
+            }
+        }
+        best = Some((distance, candidate.to_string()));
     }
+    best.map(|(_, candidate)| candidate)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Word, word, words};
+    use super::{edit_distance, suggest};
 
     #[test]
-    fn test_word() {
-        let result = word("hello world");
-        assert_eq!(0, result.index);
-        assert_eq!("hello", result.word);
-
-        let result = word(" hello world");
-        assert_eq!(1, result.index);
-        assert_eq!("hello", result.word);
-
-        let result = word(" helloworld");
-        assert_eq!(1, result.index);
-        assert_eq!("helloworld", result.word);
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("zoom", "zoom"), 0);
+        assert_eq!(edit_distance("zom", "zoom"), 1);
+        assert_eq!(edit_distance("zoom", "zom"), 1);
+        assert_eq!(edit_distance("opne", "open"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("été", "ete"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
     }
 
     #[test]
-    fn test_words() {
-        let word1 = Word {
-            index: 0,
-            word: "hello",
-        };
-        let word2 = Word {
-            index: 6,
-            word: "world",
-        };
-        assert_eq!(Some(vec![word1, word2]), words("hello world", 2));
-
-        assert_eq!(None, words("hello", 2));
-
-        let word1 = Word {
-            index: 0,
-            word: "hello",
-        };
-        let word2 = Word {
-            index: 6,
-            word: "the",
-        };
-        assert_eq!(Some(vec![word1, word2]), words("hello the world", 2));
-
-        let word1 = Word {
-            index: 1,
-            word: "hello",
-        };
-        let word2 = Word {
-            index: 7,
-            word: "world",
-        };
-        assert_eq!(Some(vec![word1, word2]), words(" hello world ", 2));
-
-        let word1 = Word {
-            index: 1,
-            word: "hello",
-        };
-        let word2 = Word {
-            index: 8,
-            word: "world",
-        };
-        assert_eq!(Some(vec![word1, word2]), words(" hello  world ", 2));
+    fn test_suggest() {
+        assert_eq!(suggest("zom", &["title", "zoom", "zoom-levels"]), Some("zoom".to_string()));
+        assert_eq!(suggest("tile", &["title", "tiles"]), Some("title".to_string()));
+        assert_eq!(suggest("opne", &["open", "quit"]), Some("open".to_string()));
+        assert_eq!(suggest("qiut", &["open", "quit"]), Some("quit".to_string()));
+        assert_eq!(suggest("opnee", &["open", "quit"]), None);
+        assert_eq!(suggest("zoom", &["zoom"]), None);
+        assert_eq!(suggest("abc", &["xyz"]), None);
+        assert_eq!(suggest("a", Vec::<String>::new()), None);
     }
 }
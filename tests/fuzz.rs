@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Check that the parser never panics, whatever the input.
+
+extern crate mg_settings;
+#[macro_use]
+extern crate mg_settings_macros;
+
+mod common;
+
+use mg_settings::{Config, Parser};
+use mg_settings::key::parse_keys;
+
+use common::Rng;
+
+/// Fragments likely to reach the edge cases of the parser.
+const FRAGMENTS: &[&str] = &[
+    "set", "nmap", "nunmap", "include", "open", "quit", "complete-next", "option", "a", "=", " = ", " ",
+    "  ", "\t", "#", "[", "]", "{", "}", ",", "\"", "'", "\\", "\\u{", "\\u{e9}", "\\n", "0", "1", "42",
+    "99999999999999999999", "1.2.3", "1e400", "0x", "0b12", "1_", "-1e-3", "+", ".", "-", "_", "true", "false", "<", ">", "<C-", "<A-",
+    "<S-", "C-", "Tab", "F1", "<Tab>", "<C->", "é", "à", "€", "😀", "ß", "\u{301}", "map", "unmap",
+];
+
+/// Characters used to exhaustively generate short lines.
+const CHARACTERS: &[char] = &[
+    ' ', '=', '[', ']', '{', '}', ',', '"', '\'', '\\', '#', '<', '>', '-', '.', '0', '9', 'a', 'C', 'é', '€',
+];
+
+#[derive(Commands, Debug, PartialEq)]
+enum CustomCommand {
+    Open(String),
+    #[count]
+    Scroll(Option<u32>),
+    Quit,
+}
+
+fn parse(input: &str) {
+    let mut parser = Parser::<CustomCommand>::new_with_config(Config {
+        application_commands: vec!["complete-next"],
+        mapping_modes: vec!["n", "i", "c"],
+        ..Config::default()
+    });
+    parser.set_include_path("tests");
+    parser.parse(input.as_bytes(), None);
+    parser.parse_line(input, Some(2));
+    let _ = parse_keys(input, 1, 1);
+}
+
+#[test]
+fn exhaustive_short_lines() {
+    let prefixes = ["", "set a = ", "set a = [", "set a = {", "nmap ", "nunmap ", "nmap <"];
+    let mut line = String::new();
+    for prefix in &prefixes {
+        for &first in CHARACTERS {
+            for &second in CHARACTERS {
+                for &third in CHARACTERS {
+                    line.clear();
+                    line.push_str(prefix);
+                    line.push(first);
+                    line.push(second);
+                    line.push(third);
+                    parse(&line);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn random_lines() {
+    let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+    let mut input = String::new();
+    for _ in 0..20_000 {
+        input.clear();
+        let count = rng.below(12) + 1;
+        for _ in 0..count {
+            if rng.below(10) == 0 {
+                input.push('\n');
+            }
+            input.push_str(FRAGMENTS[rng.below(FRAGMENTS.len())]);
+        }
+        parse(&input);
+    }
+}
+
+#[test]
+fn random_unicode() {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    let mut input = String::new();
+    for _ in 0..20_000 {
+        input.clear();
+        let count = rng.below(16) + 1;
+        for _ in 0..count {
+            let character =
+                match rng.below(3) {
+                    0 => (rng.below(0x80) as u8) as char,
+                    1 => ::std::char::from_u32(rng.below(0x800) as u32).unwrap_or('?'),
+                    _ => ::std::char::from_u32(rng.below(0x11_0000) as u32).unwrap_or('?'),
+                };
+            input.push(character);
+        }
+        parse(&input);
+    }
+}
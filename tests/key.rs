@@ -0,0 +1,214 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Check that the keys converted to strings are parsed back to the same keys.
+
+extern crate mg_settings;
+
+mod common;
+
+use std::char;
+
+use mg_settings::errors::KeyError;
+use mg_settings::key::{Key, KeySequence};
+use mg_settings::key::Key::*;
+
+use common::Rng;
+
+/// The keys other than the characters and the modifiers.
+const SPECIAL_KEYS: &[Key] = &[
+    Backspace, Delete, Down, End, Enter, Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14,
+    F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, Home, Insert, Keypad0, Keypad1, Keypad2, Keypad3, Keypad4,
+    Keypad5, Keypad6, Keypad7, Keypad8, Keypad9, KeypadComma, KeypadDivide, KeypadEnter, KeypadEqual,
+    KeypadMinus, KeypadMultiply, KeypadPlus, KeypadPoint, Left, LeftMouse, MediaNext, MediaPlayPause,
+    MediaPrevious, MediaStop, Menu, MiddleMouse, PageDown, PageUp, PrintScreen, Right, RightMouse,
+    ScrollWheelDown, ScrollWheelLeft, ScrollWheelRight, ScrollWheelUp, Space, Tab, Up, VolumeDown, VolumeMute,
+    VolumeUp, X1Mouse, X2Mouse,
+];
+
+/// The mouse buttons, which can be double or triple clicked.
+const MOUSE_BUTTONS: &[Key] = &[LeftMouse, MiddleMouse, RightMouse, X1Mouse, X2Mouse];
+
+/// The number of combinations of modifiers.
+const MODIFIERS: usize = 64;
+
+/// Generate a random key, with random modifiers.
+fn random_key(rng: &mut Rng) -> Key {
+    let key =
+        if rng.below(10) == 0 {
+            clicks(&MOUSE_BUTTONS[rng.below(MOUSE_BUTTONS.len())], rng.below(2) + 2)
+        }
+        else if rng.below(3) == 0 {
+            SPECIAL_KEYS[rng.below(SPECIAL_KEYS.len())].clone()
+        }
+        else {
+            Char(char::from_u32(rng.below(0x11_0000) as u32).unwrap_or('a'))
+        };
+    let modifiers = rng.below(MODIFIERS);
+    with_modifiers(key, modifiers)
+}
+
+/// Create the double or triple click of `button`.
+fn clicks(button: &Key, clicks: usize) -> Key {
+    if clicks == 2 {
+        DoubleClick(Box::new(button.clone()))
+    }
+    else {
+        TripleClick(Box::new(button.clone()))
+    }
+}
+
+/// Add the modifiers of the bits of `modifiers` to `key`, in an order different from the parser.
+fn with_modifiers(mut key: Key, modifiers: usize) -> Key {
+    if modifiers & 1 != 0 {
+        key = Control(Box::new(key));
+    }
+    if modifiers & 2 != 0 {
+        key = Shift(Box::new(key));
+    }
+    if modifiers & 4 != 0 {
+        key = Alt(Box::new(key));
+    }
+    if modifiers & 8 != 0 {
+        key = Hyper(Box::new(key));
+    }
+    if modifiers & 16 != 0 {
+        key = Meta(Box::new(key));
+    }
+    if modifiers & 32 != 0 {
+        key = Super(Box::new(key));
+    }
+    key
+}
+
+fn assert_round_trip(key: &Key) {
+    let normalized = key.clone().normalize();
+    assert_eq!(key.to_string().parse::<Key>(), Ok(normalized.clone()), "{:?}", key);
+    assert_eq!(normalized.to_string().parse::<Key>(), Ok(normalized.clone()), "{:?}", key);
+}
+
+#[test]
+fn exhaustive_keys() {
+    for modifiers in 0..MODIFIERS {
+        for key in SPECIAL_KEYS {
+            assert_round_trip(&with_modifiers(key.clone(), modifiers));
+        }
+        for button in MOUSE_BUTTONS {
+            assert_round_trip(&with_modifiers(clicks(button, 2), modifiers));
+            assert_round_trip(&with_modifiers(clicks(button, 3), modifiers));
+            // Modifiers inside of the click.
+            assert_round_trip(&clicks(&with_modifiers(button.clone(), modifiers), 2));
+        }
+        for code in 0..0x800 {
+            if let Some(character) = char::from_u32(code) {
+                assert_round_trip(&with_modifiers(Char(character), modifiers));
+            }
+        }
+    }
+    for character in (0..0x11_0000).filter_map(char::from_u32) {
+        assert_round_trip(&Char(character));
+    }
+}
+
+#[test]
+fn random_keys() {
+    let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+    for _ in 0..20_000 {
+        assert_round_trip(&random_key(&mut rng));
+        // Modifiers repeated in any order.
+        let key = random_key(&mut rng);
+        let modifiers = rng.below(MODIFIERS);
+        assert_round_trip(&with_modifiers(key, modifiers));
+    }
+}
+
+#[test]
+fn random_sequences() {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..5_000 {
+        let count = rng.below(8);
+        let keys: Vec<_> = (0..count).map(|_| random_key(&mut rng)).collect();
+        let normalized = KeySequence(keys.iter().cloned().map(Key::normalize).collect());
+        let sequence = KeySequence(keys);
+        assert_eq!(sequence.to_string().parse::<KeySequence>(), Ok(normalized), "{:?}", sequence);
+    }
+}
+
+#[test]
+fn escapes() {
+    assert_eq!("<lt>".parse(), Ok(Char('<')));
+    assert_eq!("<gt>".parse(), Ok(Char('>')));
+    assert_eq!(">".parse(), Ok(Char('>')));
+    assert_eq!("<Bar>".parse(), Ok(Char('|')));
+    assert_eq!("<Bslash>".parse(), Ok(Char('\\')));
+    assert_eq!("<Char-32>".parse(), Ok(Space));
+    assert_eq!("<Char-0x20>".parse(), Ok(Space));
+    assert_eq!("<Char-0x9>".parse(), Ok(Tab));
+    assert_eq!("<C-Char-9>".parse(), Ok(Control(Box::new(Tab))));
+    assert_eq!("<C-lt>".parse(), Ok(Control(Box::new(Char('<')))));
+    assert_eq!("<A-Char-0xe9>".parse(), Ok(Alt(Box::new(Char('é')))));
+
+    assert_eq!(Char('<').to_string(), "<lt>");
+    assert_eq!(Char('>').to_string(), "<gt>");
+    assert_eq!(Char('|').to_string(), "<Bar>");
+    assert_eq!(Char('\\').to_string(), "<Bslash>");
+    assert_eq!(Char(' ').to_string(), "<Char-0x20>");
+    assert_eq!(Char(' ').normalize(), Space);
+    assert_eq!(Shift(Box::new(Char('\t'))).normalize().to_string(), "<S-Tab>");
+    assert_round_trip(&Char(' '));
+    assert_round_trip(&Control(Box::new(Char('\t'))));
+    assert_eq!(Char('é').to_string(), "é");
+    assert_eq!(Char('\u{1b}').to_string(), "<Char-0x1b>");
+    assert_eq!(Control(Box::new(Char('-'))).to_string(), "<C-->");
+    assert_eq!(Control(Box::new(Char('\n'))).to_string(), "<C-Char-0xa>");
+    assert_eq!(Shift(Box::new(Control(Box::new(Char('>'))))).to_string(), "<S-C-gt>");
+    assert_eq!(Control(Box::new(DoubleClick(Box::new(LeftMouse)))).to_string(), "<C-2-LeftMouse>");
+    assert_eq!(DoubleClick(Box::new(Control(Box::new(LeftMouse)))).to_string(), "<2-C-LeftMouse>");
+}
+
+#[test]
+fn sequences() {
+    let sequence: KeySequence = "<C-w>v<lt>".parse().unwrap();
+    assert_eq!(sequence, KeySequence(vec![Control(Box::new(Char('w'))), Char('v'), Char('<')]));
+    assert_eq!(sequence.len(), 3);
+    assert_eq!(sequence.to_string(), "<C-w>v<lt>");
+    assert_eq!("".parse(), Ok(KeySequence::default()));
+    assert_eq!(KeySequence::from(vec![Space, Char('a')]).to_string(), "<Space>a");
+}
+
+#[test]
+fn errors() {
+    let error = |unexpected: &str, expected: &str, column| KeyError {
+        column,
+        expected: expected.to_string(),
+        unexpected: unexpected.to_string(),
+    };
+    assert_eq!("".parse::<Key>(), Err(error("<end of input>", "key", 1)));
+    assert_eq!("ab".parse::<Key>(), Err(error("b", "<end of input>", 2)));
+    assert_eq!("<C-w>v".parse::<Key>(), Err(error("v", "<end of input>", 6)));
+    assert_eq!("<Foo>".parse::<Key>(), Err(error("Foo", "special key", 2)));
+    assert_eq!("<Char-0xd800>".parse::<Key>(), Err(error("Char-0xd800", "character code", 2)));
+    assert_eq!("<C-2-Tab>".parse::<Key>(), Err(error("Tab", "mouse button", 6)));
+    assert_eq!("<3-ScrollWheelUp>".parse::<Key>(), Err(error("ScrollWheelUp", "mouse button", 4)));
+    assert_eq!("<2-a>".parse::<Key>(), Err(error("a", "mouse button", 4)));
+    assert_eq!("a<Tab".parse::<KeySequence>(), Err(error("(none)", ">", 6)));
+    assert_eq!("ab".parse::<Key>().unwrap_err().to_string(), "unexpected b, expecting <end of input> on column 2");
+}
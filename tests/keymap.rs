@@ -0,0 +1,204 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+extern crate mg_settings;
+#[macro_use]
+extern crate mg_settings_macros;
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use mg_settings::{Config, Parser};
+use mg_settings::key::Key::{Char, Control, Escape, Shift, Tab};
+use mg_settings::keymap::{Clock, KeyMap};
+use mg_settings::keymap::Dispatch::{Ambiguous, Matched, NoMatch, Pending};
+
+#[derive(Commands, Debug, PartialEq)]
+enum AppCommand {
+    Open(String),
+    Quit,
+}
+
+/// A clock which only moves forward when told to.
+#[derive(Clone)]
+struct ManualClock(Rc<Cell<Instant>>);
+
+impl ManualClock {
+    fn advance(&self, milliseconds: u64) {
+        self.0.set(self.0.get() + Duration::from_millis(milliseconds));
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+}
+
+fn key_map() -> (KeyMap<ManualClock>, ManualClock) {
+    let clock = ManualClock(Rc::new(Cell::new(Instant::now())));
+    let mut key_map = KeyMap::new_with_clock(clock.clone());
+    let mut parser = Parser::<AppCommand>::new_with_config(Config {
+        mapping_modes: vec!["n", "i"],
+        ..Config::default()
+    });
+    let result = parser.parse("nmap j scroll-down\nnmap gg go-top\nnmap g go\nnmap <C-w>v split\n\
+        imap jk normal-mode\nnmap x delete\nnunmap x\nset zoom = 1".as_bytes(), None);
+    assert_eq!(result.errors, vec![]);
+    let applied: Vec<_> = result.commands.iter()
+        .map(|command| key_map.apply(command))
+        .collect();
+    assert_eq!(applied, vec![true, true, true, true, true, true, true, false]);
+    key_map.map("n", &[Char('0')], "line-start");
+    (key_map, clock)
+}
+
+#[test]
+fn matches() {
+    let (mut key_map, _) = key_map();
+    assert_eq!(key_map.feed("n", Char('j')), vec![Matched("scroll-down".to_string(), None)]);
+    assert_eq!(key_map.feed("n", Control(Box::new(Char('w')))), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('v')), vec![Matched("split".to_string(), None)]);
+    assert_eq!(key_map.feed("n", Control(Box::new(Char('w')))), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('w')), vec![NoMatch(vec![Control(Box::new(Char('w'))), Char('w')])]);
+    assert_eq!(key_map.feed("n", Char('x')), vec![NoMatch(vec![Char('x')])]);
+    assert_eq!(key_map.feed("n", Escape), vec![NoMatch(vec![Escape])]);
+    assert_eq!(key_map.feed("v", Char('j')), vec![NoMatch(vec![Char('j')])]);
+
+    // Modes are independent.
+    assert_eq!(key_map.feed("i", Char('j')), vec![Pending]);
+    assert_eq!(key_map.feed("i", Char('k')), vec![Matched("normal-mode".to_string(), None)]);
+    assert_eq!(key_map.feed("i", Char('j')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('j')), vec![Matched("scroll-down".to_string(), None)]);
+}
+
+#[test]
+fn counts() {
+    let (mut key_map, _) = key_map();
+    assert_eq!(key_map.feed("n", Char('5')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('j')), vec![Matched("scroll-down".to_string(), Some(5))]);
+    assert_eq!(key_map.feed("n", Char('1')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('0')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('g')), vec![Ambiguous]);
+    assert_eq!(key_map.feed("n", Char('g')), vec![Matched("go-top".to_string(), Some(10))]);
+    // 0 is a mapping when it does not continue a count.
+    assert_eq!(key_map.feed("n", Char('0')), vec![Matched("line-start".to_string(), None)]);
+    assert_eq!(key_map.feed("n", Char('2')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('q')), vec![NoMatch(vec![Char('2'), Char('q')])]);
+    assert_eq!(key_map.feed("n", Char('9')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('9')), vec![Pending]);
+    for _ in 0..10 {
+        key_map.feed("n", Char('9'));
+    }
+    assert_eq!(key_map.feed("n", Char('j')), vec![Matched("scroll-down".to_string(), Some(u32::MAX))]);
+    // The count is given back with the keys that do not match.
+    assert_eq!(key_map.feed("i", Char('3')), vec![Pending]);
+    assert_eq!(key_map.feed("i", Char('a')), vec![NoMatch(vec![Char('3'), Char('a')])]);
+}
+
+#[test]
+fn timeouts() {
+    let (mut key_map, clock) = key_map();
+    assert_eq!(key_map.timeout_remaining(), None);
+    assert_eq!(key_map.check_timeout(), vec![]);
+    assert_eq!(key_map.feed("n", Char('3')), vec![Pending]);
+    // A count does not time out.
+    assert_eq!(key_map.timeout_remaining(), None);
+    assert_eq!(key_map.feed("n", Char('g')), vec![Ambiguous]);
+    clock.advance(400);
+    assert_eq!(key_map.timeout_remaining(), Some(Duration::from_millis(600)));
+    assert_eq!(key_map.check_timeout(), vec![]);
+    clock.advance(600);
+    assert_eq!(key_map.check_timeout(), vec![Matched("go".to_string(), Some(3))]);
+    assert_eq!(key_map.check_timeout(), vec![]);
+
+    key_map.set_timeout(Duration::from_millis(100));
+    assert_eq!(key_map.feed("n", Control(Box::new(Char('w')))), vec![Pending]);
+    clock.advance(150);
+    assert_eq!(key_map.check_timeout(), vec![NoMatch(vec![Control(Box::new(Char('w')))])]);
+
+    // Each key restarts the timeout.
+    assert_eq!(key_map.feed("i", Char('j')), vec![Pending]);
+    clock.advance(90);
+    key_map.reset();
+    assert_eq!(key_map.feed("i", Char('j')), vec![Pending]);
+    clock.advance(90);
+    assert_eq!(key_map.check_timeout(), vec![]);
+    assert_eq!(key_map.feed("i", Char('k')), vec![Matched("normal-mode".to_string(), None)]);
+}
+
+#[test]
+fn failed_continuations() {
+    let (mut key_map, clock) = key_map();
+    assert_eq!(key_map.feed("n", Char('g')), vec![Ambiguous]);
+    assert_eq!(key_map.feed("n", Char('j')),
+        vec![Matched("go".to_string(), None), Matched("scroll-down".to_string(), None)]);
+    assert_eq!(key_map.feed("n", Char('3')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('g')), vec![Ambiguous]);
+    assert_eq!(key_map.feed("n", Char('5')), vec![Matched("go".to_string(), Some(3)), Pending]);
+    assert_eq!(key_map.feed("n", Char('j')), vec![Matched("scroll-down".to_string(), Some(5))]);
+
+    key_map.map("n", &[Char('a'), Char('b')], "ab");
+    key_map.map("n", &[Char('a'), Char('b'), Char('c'), Char('d')], "abcd");
+    assert_eq!(key_map.feed("n", Char('a')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('b')), vec![Ambiguous]);
+    assert_eq!(key_map.feed("n", Char('c')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('x')),
+        vec![Matched("ab".to_string(), None), NoMatch(vec![Char('c')]), NoMatch(vec![Char('x')])]);
+
+    // The keys dispatched again can be pending.
+    key_map.map("n", &[Char('c')], "c");
+    key_map.map("n", &[Char('c'), Char('c')], "cc");
+    for &key in &['a', 'b', 'c'] {
+        key_map.feed("n", Char(key));
+    }
+    clock.advance(1000);
+    assert_eq!(key_map.check_timeout(), vec![Matched("ab".to_string(), None), Ambiguous]);
+    clock.advance(1000);
+    assert_eq!(key_map.check_timeout(), vec![Matched("c".to_string(), None)]);
+}
+
+#[test]
+fn map_and_unmap() {
+    let (mut key_map, _) = key_map();
+    assert!(key_map.unmap("n", &[Char('g')]));
+    assert!(!key_map.unmap("n", &[Char('g')]));
+    assert!(!key_map.unmap("o", &[Char('g')]));
+    assert_eq!(key_map.feed("n", Char('g')), vec![Pending]);
+    assert_eq!(key_map.feed("n", Char('g')), vec![Matched("go-top".to_string(), None)]);
+    assert!(key_map.unmap("n", &[Char('g'), Char('g')]));
+    assert_eq!(key_map.feed("n", Char('g')), vec![NoMatch(vec![Char('g')])]);
+
+    key_map.map("n", &[Char('j')], "next");
+    assert_eq!(key_map.feed("n", Char('j')), vec![Matched("next".to_string(), None)]);
+    key_map.map("n", &[], "nothing");
+    assert_eq!(key_map.feed("n", Char('5')), vec![Pending]);
+    key_map.map("n", &[Char('5')], "five");
+    assert_eq!(key_map.feed("n", Char('5')), vec![Matched("five".to_string(), None)]);
+
+    // The keys are normalized.
+    key_map.map("n", &[Control(Box::new(Shift(Box::new(Tab))))], "previous-tab");
+    assert_eq!(key_map.feed("n", Shift(Box::new(Control(Box::new(Tab))))), vec![Matched("previous-tab".to_string(), None)]);
+    key_map.map("n", &[Shift(Box::new(Char('a')))], "append");
+    assert_eq!(key_map.feed("n", Char('A')), vec![Matched("append".to_string(), None)]);
+    assert!(key_map.unmap("n", &[Char('A')]));
+}
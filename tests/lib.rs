@@ -23,15 +23,26 @@ extern crate mg_settings;
 #[macro_use]
 extern crate mg_settings_macros;
 
-use mg_settings::{Config, EnumFromStr, Parser, ParseResult};
-use mg_settings::Command::{self, App, Custom, Map, Set, Unmap};
-use mg_settings::errors::Error;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::error::Error as StdError;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use mg_settings::{json, Config, EnumFromStr, EnumMetaData, Parser, ParseResult, SetOperator, SettingCompletion, ValueType};
+use mg_settings::Command::{self, App, Custom, Flag, Map, Query, Reset, Set, Toggle, Unmap, Update};
+use mg_settings::errors::{Diagnostic, Error, ErrorType, ParseError, Renderer, SettingError, Severity};
+use mg_settings::position::{Pos, Span};
+use mg_settings::settings::Settings;
+use mg_settings::key::{CanonicalKey, Key, Modifiers, parse_keys};
 use mg_settings::key::Key::{
     Alt,
     Backspace,
     Char,
     Control,
     Delete,
+    DoubleClick,
     Down,
     End,
     Enter,
@@ -48,18 +59,36 @@ use mg_settings::key::Key::{
     F10,
     F11,
     F12,
+    F13,
+    F24,
     Home,
+    Hyper,
     Insert,
+    Keypad0,
+    KeypadEnter,
+    KeypadPlus,
     Left,
+    LeftMouse,
+    MediaPlayPause,
+    Menu,
+    Meta,
+    MiddleMouse,
     PageDown,
     PageUp,
+    PrintScreen,
     Right,
+    RightMouse,
+    ScrollWheelUp,
     Shift,
     Space,
+    Super,
     Tab,
+    TripleClick,
     Up,
+    VolumeUp,
+    X1Mouse,
 };
-use mg_settings::Value::{Bool, Float, Int, Str};
+use mg_settings::Value::{self, Bool, Float, Int, List, Str};
 
 use CustomCommand::*;
 
@@ -127,6 +156,112 @@ enum CustomCommand {
     WinOpen(String),
 }
 
+#[derive(Default, Settings)]
+struct AppSettings {
+    headers: BTreeMap<String, String>,
+    private_browsing: bool,
+    search_engines: Vec<String>,
+    scale: f64,
+    sizes: Vec<i64>,
+    themes: Vec<Theme>,
+    title: String,
+    zoom: i64,
+    zoom_levels: HashMap<String, i64>,
+}
+
+#[derive(Debug, PartialEq, Settings)]
+struct DefaultSettings {
+    #[default = true]
+    hints: bool,
+    #[default = 1]
+    scale: f64,
+    #[default = "vec![\"duckduckgo\".to_string()]"]
+    search_engines: Vec<String>,
+    #[default = "Theme::Light"]
+    theme: Theme,
+    #[default = "mg"]
+    title: String,
+    zoom: i64,
+}
+
+#[derive(Settings)]
+struct TypedSettings {
+    download_dir: PathBuf,
+    hint_char: char,
+    #[default = "~/.config"]
+    config_dir: PathBuf,
+    opacity: f32,
+    port: u16,
+    proxy: Option<String>,
+    scroll: i8,
+    tab_limit: Option<usize>,
+    timeouts: Vec<u32>,
+}
+
+#[derive(Default, Settings)]
+struct ValidatedSettings {
+    #[one_of("left", "right")]
+    align: String,
+    #[range(min = 0.0, max = 1)]
+    opacity: f64,
+    #[range(min = "-10")]
+    scroll: Option<i32>,
+    #[non_empty]
+    search_engines: Vec<String>,
+    #[validate = "validate_url"]
+    #[non_empty]
+    url: String,
+    #[range(min = 25, max = 500)]
+    zoom: u32,
+}
+
+fn validate_url(url: &str) -> Result<(), String> {
+    if url.starts_with("https://") {
+        Ok(())
+    }
+    else {
+        Err(format!("{} is not an https URL", url))
+    }
+}
+
+#[derive(Default, Settings)]
+struct HintSettings {
+    chars: String,
+    #[help(text = "Show the hints")]
+    enabled: bool,
+    size: i64,
+}
+
+#[derive(Default, Settings)]
+struct AliasedSettings {
+    #[alias = "private"]
+    private_browsing: bool,
+    #[settings(renamed_from = "zoom-level", note = "the level is now a percentage")]
+    zoom: i64,
+    #[settings(nested)]
+    hints: AliasedHintSettings,
+}
+
+#[derive(Default, Settings)]
+struct AliasedHintSettings {
+    #[settings(renamed_from = "characters")]
+    chars: String,
+}
+
+#[derive(Default, Settings)]
+struct NestedSettings {
+    #[settings(nested)]
+    hints: HintSettings,
+    #[help(text = "The window title")]
+    title: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Setting)]
+enum Theme {
+    Dark,
+    Light,
+}
+
 type CommandParser = Parser<CustomCommand>;
 
 #[test]
@@ -176,37 +311,39 @@ fn parser_errors() {
     assert_error!(" set 5 5", "unexpected 5, expecting identifier on line 1, column 6");
     assert_error!("set  5 5", "unexpected 5, expecting identifier on line 1, column 6");
     assert_error!("5", "unexpected 5, expecting command or comment on line 1, column 1");
-    assert_error!(" ste option1 = 42", "unexpected ste, expecting command or comment on line 1, column 2");
+    assert_error!(" ste option1 = 42", "unexpected ste, expecting command or comment, did you mean `set`? on line 1, column 2");
     assert_error!("set option1 < 42", "unexpected <, expecting = on line 1, column 13");
     assert_error!(" set option1 < 42", "unexpected <, expecting = on line 1, column 14");
     assert_error!("set option1 =", "unexpected <end of line>, expecting value on line 1, column 14");
     assert_error!("set", "unexpected <end of line>, expecting command arguments on line 1, column 4");
-    assert_error!("set option1", "unexpected <end of line>, expecting = on line 1, column 12");
+    assert_error!("set option1 42", "unexpected 42, expecting = on line 1, column 13");
+    assert_error!("set option1! 42", "unexpected 42, expecting <end of line> on line 1, column 14");
+    assert_error!("set option1!?", "unexpected option1!, expecting identifier on line 1, column 5");
     assert_error!("include", "unexpected <end of line>, expecting command arguments on line 1, column 8");
     assert_error_config!("nmap a", "unexpected <end of line>, expecting mapping action on line 1, column 7");
     assert_error_config!("nmap", "unexpected <end of line>, expecting command arguments on line 1, column 5");
-    assert_error_config!("nmap <C-@> :open",
-        "unexpected @, expecting A-Z or special key on line 1, column 9");
+    assert_error_config!("nmap <C-@@> :open",
+        "unexpected @@, expecting character or special key on line 1, column 9");
     assert_error_config!("nmap <C-o@> :open",
         "unexpected o@, expecting one character on line 1, column 9");
     assert_error_config!("nmap <C-TE> :open",
         "unexpected TE, expecting one character on line 1, column 9");
     assert_error_config!("nmap <Test> :open",
         "unexpected Test, expecting special key on line 1, column 7");
-    assert_error_config!("mmap o :open", "unexpected mmap, expecting command or comment on line 1, column 1");
+    assert_error_config!("mmap o :open", "unexpected mmap, expecting command or comment, did you mean `nmap`? on line 1, column 1");
     assert_error_config!("nunmap <F1> :help", "unexpected :help, expecting <end of line> on line 1, column 13");
     assert_error_config!("include file.conf my-other-config", "unexpected my-other-config, expecting <end of line> on line 1, column 19");
     assert_error_config!("include config my-other-config",
         "unexpected my-other-config, expecting <end of line> on line 1, column 16",
-        "failed to open included file `tests/config`: No such file or directory (os error 2)");
+        "cannot access file `tests/config`: No such file or directory (os error 2)");
     assert_error!("open", "unexpected <end of line>, expecting command arguments on line 1, column 5");
     assert_error_config!("nmap <F1 :help",
         "unexpected (none), expecting > on line 1, column 9");
     assert_error_config!("nmap <F> :help",
         "unexpected F, expecting special key on line 1, column 7");
-    assert_error_config!("nmap\nnmap <C-@> :open",
+    assert_error_config!("nmap\nnmap <C-@@> :open",
         "unexpected <end of line>, expecting command arguments on line 1, column 5",
-        "unexpected @, expecting A-Z or special key on line 2, column 9");
+        "unexpected @@, expecting character or special key on line 2, column 9");
 }
 
 #[test]
@@ -216,6 +353,159 @@ fn include_command() {
     assert_eq!(parse_string_no_include_path("include tests/file.conf"), vec![Set("option1".to_string(), Int(5))]);
 }
 
+#[test]
+fn include_cycle() {
+    assert_error_config!("include include/self.conf",
+        "recursive include: tests/include/self.conf -> tests/include/self.conf on line 1, column 9");
+    assert_error_config!("include include/a.conf",
+        "recursive include: tests/include/a.conf -> tests/include/b.conf -> tests/include/a.conf on line 2, column 9");
+    assert_eq!(parse_string("include include/a.conf"), vec![Set("option2".to_string(), Int(6))]);
+
+    let mut parser = CommandParser::new();
+    parser.set_include_path("tests");
+    let result = parser.parse_file("tests/include/a.conf", None);
+    assert_eq!(result.commands, vec![Set("option2".to_string(), Int(6))]);
+    compare_errors!(result.errors,
+        ["recursive include: tests/include/a.conf -> tests/include/b.conf -> tests/include/a.conf on line 2, column 9"]);
+}
+
+#[test]
+fn io_errors() {
+    let mut parser = CommandParser::new();
+    parser.set_include_path("tests");
+    let result = parser.parse("include missing.conf".as_bytes(), None);
+    match result.errors[0] {
+        Error::Io { ref path, ref source } => {
+            assert_eq!(*path, Some(PathBuf::from("tests/missing.conf")));
+            assert_eq!(source.kind(), io::ErrorKind::NotFound);
+        },
+        ref error => panic!("expecting an io error, got {:?}", error),
+    }
+    assert!(result.errors[0].to_string().starts_with("cannot access file `tests/missing.conf`: "));
+    let source = result.errors[0].source().and_then(|source| source.downcast_ref::<io::Error>()).unwrap();
+    assert_eq!(source.kind(), io::ErrorKind::NotFound);
+
+    let result = parser.parse(&b"set a = 1\nset b = \xFF\nset c = 3"[..], None);
+    assert_eq!(result.commands, vec![Set("a".to_string(), Int(1)), Set("c".to_string(), Int(3))]);
+    match result.errors[0] {
+        Error::Io { path: None, ref source } => assert_eq!(source.kind(), io::ErrorKind::InvalidData),
+        ref error => panic!("expecting an io error, got {:?}", error),
+    }
+    assert_eq!(result.errors[0].to_string(), "invalid utf-8 sequence of 1 bytes from index 8");
+
+    let error: Error = SettingError::InvalidKey {
+        key: "docs.rs".to_string(),
+        error: Box::new(SettingError::Invalid("too big".to_string())),
+    }.into();
+    assert_eq!(error.to_string(), "invalid value for key docs.rs: invalid value: too big");
+    let source = error.source().and_then(|source| source.downcast_ref::<SettingError>()).unwrap();
+    assert!(matches!(*source, SettingError::InvalidKey { .. }));
+    let cause = error.source().and_then(|source| source.source()).unwrap();
+    assert_eq!(cause.downcast_ref::<SettingError>(), Some(&SettingError::Invalid("too big".to_string())));
+    let error: Box<dyn StdError> = Box::new(ParseError::new(ErrorType::Parse, "1".to_string(),
+        "value".to_string(), Pos::new(1, 5)));
+    assert!(error.source().and_then(|source| source.downcast_ref::<ParseError>()).is_some());
+    assert_eq!(Error::from("message"), Error::Msg("message".to_string()));
+}
+
+#[test]
+fn include_depth() {
+    let mut parser = CommandParser::new_with_config(Config {
+        max_include_depth: 1,
+        ..Config::default()
+    });
+    parser.set_include_path("tests");
+    let result = parser.parse("include include/nested.conf".as_bytes(), None);
+    assert!(result.commands.is_empty());
+    compare_errors!(result.errors,
+        ["unexpected include of tests/file.conf, expecting at most 1 nested includes on line 1, column 9"]);
+    assert_eq!(parse_string("include include/nested.conf"), vec![Set("option1".to_string(), Int(5))]);
+}
+
+#[test]
+fn list_value() {
+    assert_setting!("search-engines", "[duckduckgo, github, \"docs rs\"]", Set("search-engines".to_string(),
+        List(vec![Str("duckduckgo".to_string()), Str("github".to_string()), Str("docs rs".to_string())])));
+    assert_setting!("sizes", "[]", Set("sizes".to_string(), List(vec![])));
+    assert_setting!("sizes", "[1, 2.5, true,]", Set("sizes".to_string(), List(vec![Int(1), Float(2.5), Bool(true)])));
+    assert_setting!("sizes", "[[1], [2, 3]] # Comment.", Set("sizes".to_string(),
+        List(vec![List(vec![Int(1)]), List(vec![Int(2), Int(3)])])));
+    assert_error!("set sizes = [1, 2", "unexpected <end of line>, expecting , or ] on line 1, column 18");
+    assert_error!("set sizes = [1 2]", "unexpected 2, expecting , or ] on line 1, column 16");
+    assert_error!("set sizes = [1, , 2]", "unexpected ,, expecting value on line 1, column 17");
+    assert_error!("set sizes = [1] 2", "unexpected 2, expecting <end of line> on line 1, column 17");
+    assert_error!("set sizes = [\"docs rs]", "unexpected <end of line>, expecting \" on line 1, column 23");
+}
+
+#[test]
+fn list_setting() {
+    let mut settings = AppSettings::default();
+    let value = List(vec![Str("duckduckgo".to_string()), Str("docs rs".to_string())]);
+    settings.set_value(AppSettings::to_variant("search-engines", value).unwrap());
+    assert_eq!(settings.search_engines, vec!["duckduckgo".to_string(), "docs rs".to_string()]);
+    settings.set_value(AppSettings::to_variant("sizes", List(vec![Int(1), Int(2)])).unwrap());
+    assert_eq!(settings.sizes, vec![1, 2]);
+    let value = List(vec![Str("dark".to_string()), Str("light".to_string())]);
+    settings.set_value(AppSettings::to_variant("themes", value).unwrap());
+    assert_eq!(settings.themes, vec![Theme::Dark, Theme::Light]);
+
+    let error = AppSettings::to_variant("sizes", Int(1)).err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting list of integer, but found int");
+    let error = AppSettings::to_variant("sizes", List(vec![Int(1), Str("2".to_string())])).err().unwrap();
+    assert_eq!(error.to_string(), "invalid value at index 1: wrong value type: expecting integer, but found string");
+    let error = AppSettings::to_variant("themes", List(vec![Str("blue".to_string())])).err().unwrap();
+    assert_eq!(error.to_string(), "invalid value at index 0: unknown choice blue, expecting one of: dark, light");
+    let value = List(vec![Int(1), Str("foo".to_string()), Int(3)]);
+    let error = AppSettings::to_variant("sizes", value).err().unwrap();
+    assert_eq!(Diagnostic::from(&error).code, "invalid-element");
+    assert_eq!(error, Error::Setting(SettingError::InvalidElement {
+        index: 1,
+        error: Box::new(SettingError::WrongType {
+            actual: "string".to_string(),
+            expected: "integer".to_string(),
+        }),
+    }));
+}
+
+#[test]
+fn map_value() {
+    let mut map = BTreeMap::new();
+    map.insert("user-agent".to_string(), Str("x".to_string()));
+    map.insert("dnt".to_string(), Bool(true));
+    assert_setting!("headers", "{ user-agent = \"x\", dnt = true }", Set("headers".to_string(), Value::Map(map)));
+    assert_setting!("headers", "{}", Set("headers".to_string(), Value::Map(BTreeMap::new())));
+    let mut map = BTreeMap::new();
+    map.insert("docs.rs".to_string(), Int(150));
+    map.insert("my site".to_string(), List(vec![Int(1)]));
+    assert_setting!("zoom-levels", "{docs.rs=150, \"my site\" = [1],} # Comment.",
+        Set("zoom-levels".to_string(), Value::Map(map)));
+    assert_error!("set headers = { dnt = true", "unexpected <end of line>, expecting , or } on line 1, column 27");
+    assert_error!("set headers = { dnt true }", "unexpected true, expecting = on line 1, column 21");
+    assert_error!("set headers = { = true }", "unexpected =, expecting key on line 1, column 17");
+    assert_error!("set headers = { dnt = 1, dnt = 2 }", "unexpected dnt, expecting unique key on line 1, column 26");
+}
+
+#[test]
+fn map_setting() {
+    let mut settings = AppSettings::default();
+    let mut map = BTreeMap::new();
+    map.insert("dnt".to_string(), Str("1".to_string()));
+    settings.set_value(AppSettings::to_variant("headers", Value::Map(map)).unwrap());
+    assert_eq!(settings.headers.get("dnt"), Some(&"1".to_string()));
+    let mut map = BTreeMap::new();
+    map.insert("docs.rs".to_string(), Int(150));
+    settings.set_value(AppSettings::to_variant("zoom-levels", Value::Map(map)).unwrap());
+    assert_eq!(settings.zoom_levels.get("docs.rs"), Some(&150));
+
+    let error = AppSettings::to_variant("zoom-levels", Int(1)).err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting map of integer, but found int");
+    let mut map = BTreeMap::new();
+    map.insert("docs.rs".to_string(), Int(150));
+    map.insert("github.com".to_string(), Float(1.5));
+    let error = AppSettings::to_variant("zoom-levels", Value::Map(map)).err().unwrap();
+    assert_eq!(error.to_string(), "invalid value for key github.com: wrong value type: expecting integer, but found float");
+}
+
 #[test]
 fn line() {
     let result = parse_line_with_config("nmap o :open");
@@ -260,16 +550,56 @@ fn map_command() {
     assert_single_key!("C-Z", Control(Box::new(Char('Z'))));
     assert_single_key!("C-o", Control(Box::new(Char('o'))));
     assert_single_key!("A-o", Alt(Box::new(Char('o'))));
-    assert_single_key!("S-A", Shift(Box::new(Char('A'))));
+    assert_single_key!("S-A", Char('A'));
+    assert_single_key!("S-a", Char('A'));
+    assert_single_key!("A-S-C-a", Control(Box::new(Alt(Box::new(Char('A'))))));
     assert_single_key!("C-Tab", Control(Box::new(Tab)));
     assert_single_key!("S-Tab", Shift(Box::new(Tab)));
     assert_single_key!("C-S-Tab", Control(Box::new(Shift(Box::new(Tab)))));
     assert_single_key!("C-A-S-Tab", Control(Box::new(Alt(Box::new(Shift(Box::new(Tab)))))));
     assert_single_key!("S-C-Tab", Control(Box::new(Shift(Box::new(Tab)))));
+    assert_single_key!("F13", F13);
+    assert_single_key!("F24", F24);
+    assert_single_key!("k0", Keypad0);
+    assert_single_key!("kPlus", KeypadPlus);
+    assert_single_key!("kEnter", KeypadEnter);
+    assert_single_key!("PrintScreen", PrintScreen);
+    assert_single_key!("Menu", Menu);
+    assert_single_key!("MediaPlayPause", MediaPlayPause);
+    assert_single_key!("VolumeUp", VolumeUp);
+    assert_single_key!("C-@", Control(Box::new(Char('@'))));
+    assert_single_key!("A-é", Alt(Box::new(Char('é'))));
+    assert_single_key!("D-c", Super(Box::new(Char('c'))));
+    assert_single_key!("M-x", Meta(Box::new(Char('x'))));
+    assert_single_key!("H-S-F13", Hyper(Box::new(Shift(Box::new(F13)))));
+    assert_single_key!("LeftMouse", LeftMouse);
+    assert_single_key!("X1Mouse", X1Mouse);
+    assert_single_key!("C-ScrollWheelUp", Control(Box::new(ScrollWheelUp)));
+    assert_single_key!("2-LeftMouse", DoubleClick(Box::new(LeftMouse)));
+    assert_single_key!("S-3-RightMouse", Shift(Box::new(TripleClick(Box::new(RightMouse)))));
+    assert_single_key!("2-C-MiddleMouse", Control(Box::new(DoubleClick(Box::new(MiddleMouse)))));
+    assert_single_key!("S-M-H-D-C-A-Tab",
+        Hyper(Box::new(Super(Box::new(Meta(Box::new(Control(Box::new(Alt(Box::new(Shift(Box::new(Tab)))))))))))));
 
     assert_single_char!('o');
     assert_single_char!('-');
     assert_single_char!('+');
+    assert_single_char!('é');
+
+    assert_eq!(parse_string_with_config("nmap <C-S-a>b :open"),
+        vec![Map { action: ":open".to_string(),
+            keys: vec![Control(Box::new(Char('A'))), Char('b')], mode: "n".to_string() }]);
+    assert_eq!(parse_string_with_config("nmap éa :open"),
+        vec![Map { action: ":open".to_string(), keys: vec![Char('é'), Char('a')], mode: "n".to_string() }]);
+    assert_error_config!("nmap <C-> :open", "unexpected , expecting character or special key on line 1, column 9");
+    assert_error_config!("nmap é\u{1} :open", "unexpected \u{1}, expecting key on line 1, column 7");
+    assert_error_config!("nmap <2-Tab> :open", "unexpected Tab, expecting mouse button on line 1, column 9");
+    assert_eq!(parse_string_with_config("nmap <C-ScrollWheelUp> zoom-in"),
+        vec![Map { action: "zoom-in".to_string(), keys: vec![Control(Box::new(ScrollWheelUp))],
+            mode: "n".to_string() }]);
+    assert_eq!(parse_string_with_config("nmap é€1😀 :open"),
+        vec![Map { action: ":open".to_string(), keys: vec![Char('é'), Char('€'), Char('1'), Char('😀')],
+            mode: "n".to_string() }]);
 
     assert_eq!(parse_string_with_config("nmap Oo :open"),
         vec![Map { action: ":open".to_string(), keys: vec![Char('O'), Char('o')], mode: "n".to_string() }]);
@@ -277,12 +607,708 @@ fn map_command() {
         vec![Map { action: ":open".to_string(),
             keys: vec![Control(Box::new(Char('O'))), Char('o')], mode: "n".to_string() }]);
 
-    let result = parse_with_config("nmap\nnmap <C-@> :open\nnmap o :open");
+    let result = parse_with_config("nmap\nnmap <C-@@> :open\nnmap o :open");
     assert_eq!(result.commands,
         vec![Map { action: ":open".to_string(), keys: vec![Char('o')], mode: "n".to_string() }]);
     compare_errors!(result.errors,
         ["unexpected <end of line>, expecting command arguments on line 1, column 5",
-        "unexpected @, expecting A-Z or special key on line 2, column 9"]);
+        "unexpected @@, expecting character or special key on line 2, column 9"]);
+}
+
+#[test]
+fn canonical_keys() {
+    let control_shift_tab = Control(Box::new(Shift(Box::new(Tab))));
+    assert_eq!(Shift(Box::new(Control(Box::new(Tab)))).canonical(), control_shift_tab.canonical());
+    assert_eq!(Shift(Box::new(Control(Box::new(Shift(Box::new(Tab)))))).normalize(), control_shift_tab);
+    assert_eq!(Shift(Box::new(Char('a'))).canonical(), Char('A').canonical());
+    assert_eq!(Shift(Box::new(Char('A'))).normalize(), Char('A'));
+    assert_eq!(Shift(Box::new(Char('é'))).normalize(), Char('É'));
+    assert_eq!(Shift(Box::new(Char('-'))).normalize(), Shift(Box::new(Char('-'))));
+    assert_ne!(Control(Box::new(Char('a'))).canonical(), Control(Box::new(Char('A'))).canonical());
+
+    let key = Alt(Box::new(Shift(Box::new(Control(Box::new(Tab)))))).canonical();
+    assert_eq!(*key.key(), Tab);
+    assert_eq!(key.modifiers(), Modifiers { alt: true, control: true, shift: true, ..Modifiers::new() });
+    assert_eq!(key.to_string(), "<C-A-S-Tab>");
+    assert_eq!(Key::from(key), Control(Box::new(Alt(Box::new(Shift(Box::new(Tab)))))));
+    assert_eq!(CanonicalKey::new(Char('x'), Modifiers { control: true, ..Modifiers::new() }),
+        Control(Box::new(Char('x'))).canonical());
+    assert!(Char('x').canonical().modifiers().is_empty());
+
+    let keys: HashSet<_> = ["<C-S-Tab>", "<S-C-Tab>", "<S-a>", "A", "<C-S-a>", "<C-A>"].iter()
+        .map(|keys| parse_keys(keys, 1, 1).unwrap())
+        .collect();
+    assert_eq!(keys.len(), 3);
+}
+
+#[test]
+fn malformed_numbers() {
+    assert_error!("set option1 = 99999999999999999999",
+        "unexpected 99999999999999999999, expecting integer between -9223372036854775808 and 9223372036854775807 on line 1, column 15");
+    assert_error!("set option1 = 1.2.3", "unexpected 1.2.3, expecting number on line 1, column 15");
+    assert_error!("set option1 = [1, 2e]", "unexpected 2e, expecting number on line 1, column 19");
+    assert_error!("set option1 = 1e400", "unexpected 1e400, expecting finite float on line 1, column 15");
+    assert_setting!("option1", "9223372036854775807", Set("option1".to_string(), Int(9223372036854775807)));
+    assert_setting!("option1", "1.2.3 4", Set("option1".to_string(), Str("1.2.3 4".to_string())));
+    // Words starting with a digit which are not number literals are strings.
+    assert_setting!("option1", "800x600", Set("option1".to_string(), Str("800x600".to_string())));
+    assert_setting!("option1", "5px", Set("option1".to_string(), Str("5px".to_string())));
+    assert_setting!("option1", "2fa", Set("option1".to_string(), Str("2fa".to_string())));
+    assert_setting!("option1", "2020-01-01", Set("option1".to_string(), Str("2020-01-01".to_string())));
+    assert_setting!("option1", "[5px, 1]",
+        Set("option1".to_string(), List(vec![Str("5px".to_string()), Int(1)])));
+}
+
+#[test]
+fn nested_settings() {
+    assert_eq!(parse_string("set hints.chars = asdf"), vec![Set("hints.chars".to_string(), Str("asdf".to_string()))]);
+    assert_eq!(parse_string("set hints.enabled!"), vec![Toggle("hints.enabled".to_string())]);
+    assert_error!("set hints. = asdf", "unexpected hints., expecting identifier on line 1, column 5");
+    assert_error!("set .chars = asdf", "unexpected .chars, expecting identifier on line 1, column 5");
+
+    let mut settings = NestedSettings::default();
+    settings.set_value(NestedSettings::to_variant("hints.chars", Str("asdf".to_string())).unwrap());
+    settings.set_value(NestedSettings::to_variant("title", Str("mg".to_string())).unwrap());
+    assert_eq!(settings.hints.chars, "asdf");
+    assert_eq!(settings.title, "mg");
+    assert_eq!(settings.get_value("hints.chars").unwrap(), Str("asdf".to_string()));
+
+    settings.set_value(settings.toggle_variant("hints.enabled").unwrap());
+    assert!(settings.hints.enabled);
+    settings.set_value(NestedSettings::flag_variant("nohints.enabled").unwrap());
+    assert!(!settings.hints.enabled);
+    settings.set_value(NestedSettings::flag_variant("hints.enabled").unwrap());
+    assert!(settings.hints.enabled);
+    settings.set_value(settings.update_variant("hints.size", SetOperator::Add, Int(2)).unwrap());
+    assert_eq!(settings.hints.size, 2);
+    settings.reset("hints.size").unwrap();
+    assert_eq!(settings.hints.size, 0);
+
+    let error = NestedSettings::to_variant("hints.color", Str("red".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "no setting named hints.color");
+    let error = NestedSettings::to_variant("hints.size", Str("red".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting integer, but found string");
+    let error = NestedSettings::to_variant("hints", Str("red".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "no setting named hints");
+
+    assert_eq!(NestedSettings::setting_names(), vec!["hints.chars", "hints.enabled", "hints.size", "title"]);
+    assert_eq!(NestedSettings::setting_type("hints.enabled").unwrap(), ValueType::Bool);
+    let metadata = NestedSettings::get_metadata();
+    assert_eq!(metadata["hints.enabled"].help_text, "Show the hints");
+    assert_eq!(metadata["title"].help_text, "The window title");
+    assert_eq!(metadata.len(), 4);
+    let completions = NestedSettings::get_value_completions();
+    assert_eq!(completions["hints.enabled"], vec!["true", "false"]);
+    assert_eq!(completions.len(), 4);
+}
+
+#[test]
+fn diagnostics() {
+    let result = parse_with_config("set zoom = 1\nnmap o open\nset zoom = 2\nnmap o tabopen\nnunmap o\nnmap o open\n\
+        nunmap <C-a>\nunset zoom\nset zoom = 3\nset title = mg\nunknown");
+    let warnings: Vec<_> = result.warnings.iter()
+        .map(|warning| (warning.severity, warning.code, warning.to_string()))
+        .collect();
+    assert_eq!(warnings, vec![
+        (Severity::Warning, "duplicate-setting", "setting zoom is already set on line 1 on line 3, column 5".to_string()),
+        (Severity::Warning, "overridden-mapping",
+            "mapping o in mode n overrides the mapping on line 2 on line 4, column 6".to_string()),
+        (Severity::Hint, "unmapped-key", "<C-a> is not mapped in mode n on line 7, column 8".to_string()),
+    ]);
+    let diagnostics = result.diagnostics();
+    assert_eq!(diagnostics.len(), 4);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].code, "unknown-command");
+    assert_eq!(diagnostics[0].message, "unexpected unknown, expecting command or comment");
+    assert_eq!(diagnostics[0].span.as_ref().map(|span| (span.start.line, span.start.column)), Some((11, 1)));
+
+    // Each parsed line and each included file is checked separately.
+    let mut parser = CommandParser::new_with_config(Config {
+        mapping_modes: vec!["n"],
+        ..Config::default()
+    });
+    assert!(parser.parse_line("set zoom = 1", None).warnings.is_empty());
+    assert!(parser.parse_line("set zoom = 2", None).warnings.is_empty());
+    assert!(parser.parse_line("nmap o open", None).warnings.is_empty());
+    assert!(parser.parse_line("nunmap o", None).warnings.is_empty());
+    assert_eq!(parser.parse_line("nunmap o", None).warnings[0].code, "unmapped-key");
+}
+
+#[test]
+fn spans() {
+    let mut parser = CommandParser::new();
+    parser.set_include_path("tests");
+    let result = parser.parse("# Comment.\r\n  set été = 1 # Comment.\ninclude include/a.conf\nset a = [1\n".as_bytes(), None);
+    assert_eq!(result.commands.len(), result.spans.len());
+    assert_eq!(result.spans[0], Span {
+        bytes: 14..27,
+        end: Pos::new(2, 14),
+        source: None,
+        start: Pos::new(2, 3),
+    });
+    assert_eq!(result.spans[1], Span {
+        bytes: 0..15,
+        end: Pos::new(1, 16),
+        source: Some(PathBuf::from("tests/include/b.conf")),
+        start: Pos::new(1, 1),
+    });
+
+    let spans: Vec<_> = result.errors.iter()
+        .map(|error| match *error {
+            Error::Parse(ref error) => *error.span.clone().unwrap(),
+            _ => panic!("expecting a parse error"),
+        })
+        .collect();
+    assert_eq!(spans, vec![
+        Span {
+            bytes: 24..24,
+            end: Pos::new(2, 9),
+            source: Some(PathBuf::from("tests/include/b.conf")),
+            start: Pos::new(2, 9),
+        },
+        Span {
+            bytes: 72..72,
+            end: Pos::new(4, 11),
+            source: None,
+            start: Pos::new(4, 11),
+        },
+    ]);
+    assert_eq!(spans[0].to_string(), "tests/include/b.conf:2:9");
+
+    let mut parser = CommandParser::new();
+    let result = parser.parse_file("tests/file.conf", None);
+    assert_eq!(result.spans[0].source, Some(PathBuf::from("tests/file.conf")));
+    let result = parser.parse_file("tests/missing.conf", None);
+    assert!(result.errors[0].to_string().starts_with("cannot access file `tests/missing.conf`: "));
+
+    let result = parse_with_config("set zoom = 2\nset title = 1\nset zoom += x\nnmap o open");
+    let mut settings = AppSettings::default();
+    let errors = settings.apply(&result);
+    assert_eq!(settings.zoom, 2);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].node.to_string(), "wrong value type: expecting string, but found int");
+    assert_eq!(errors[0].span.start, Pos::new(2, 1));
+    assert_eq!(errors[1].span.start, Pos::new(3, 1));
+    let diagnostic = Diagnostic::from(&errors[1]);
+    assert_eq!(diagnostic.code, "wrong-type");
+    assert_eq!(diagnostic.span.unwrap().bytes, 27..40);
+}
+
+#[test]
+fn apply_errors_in_values() {
+    let result = parse_with_config("set zoom-levels = {a = 1, b = x}\nset sizes = [1, x, 3]\nset sizes += [4, y]");
+    assert_eq!(result.value_spans[0].len(), 2);
+    let mut settings = AppSettings::default();
+    let errors = settings.apply(&result);
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0].node.to_string(), "invalid value for key b: wrong value type: expecting integer, but found string");
+    assert_eq!(errors[0].span.start, Pos::new(1, 27));
+    assert_eq!(errors[0].span.end, Pos::new(1, 28));
+    assert_eq!(errors[1].span.start, Pos::new(2, 17));
+    assert_eq!(errors[1].span.end, Pos::new(2, 18));
+    assert_eq!(errors[2].span.start, Pos::new(3, 18));
+}
+
+#[test]
+fn render_diagnostics() {
+    let source = "set zoom = 1\nset zoom = [1 2]\n\tunknown\ninclude include/self.conf\nset zoom = 2\n";
+    let mut parser = CommandParser::new();
+    parser.set_include_path("tests");
+    let result = parser.parse(source.as_bytes(), None);
+    let included_source = fs::read_to_string("tests/include/self.conf").unwrap();
+    let sources = |path: Option<&Path>|
+        match path {
+            None => Some(source),
+            Some(path) if path == Path::new("tests/include/self.conf") => Some(included_source.as_str()),
+            Some(_) => None,
+        };
+    let renderer = Renderer::default();
+    assert_eq!(renderer.render_result(&result, sources), "\
+error[parse]: unexpected 2
+ --> <input>:2:15
+  |
+2 | set zoom = [1 2]
+  |               ^
+  = note: expecting , or ]
+
+error[unknown-command]: unexpected unknown
+ --> <input>:3:2
+  |
+3 | \tunknown
+  | \t^^^^^^^
+  = note: expecting command or comment
+
+error[recursive-include]: recursive include: tests/include/self.conf -> tests/include/self.conf
+ --> tests/include/self.conf:1:9
+  |
+1 | include include/self.conf
+  |         ^
+
+warning[duplicate-setting]: setting zoom is already set on line 1
+ --> <input>:5:5
+  |
+5 | set zoom = 2
+  |     ^^^^
+");
+
+    let error = Error::Msg("failed to open file".to_string());
+    assert_eq!(renderer.render_error(&error, sources), "error[other]: failed to open file\n");
+    // The source line is not shown when its text is not available.
+    assert_eq!(renderer.render_error(&result.errors[2], |_| None), "\
+error[recursive-include]: recursive include: tests/include/self.conf -> tests/include/self.conf
+ --> tests/include/self.conf:1:9
+");
+
+    let renderer = Renderer {
+        colors: true,
+        ..Renderer::default()
+    };
+    assert_eq!(renderer.render_diagnostic(&result.warnings[0], sources), "\
+\x1b[1;33mwarning[duplicate-setting]\x1b[0m\x1b[1m: setting zoom is already set on line 1\x1b[0m
+ \x1b[1;34m-->\x1b[0m <input>:5:5
+  \x1b[1;34m|\x1b[0m
+\x1b[1;34m5 |\x1b[0m set zoom = 2
+  \x1b[1;34m|\x1b[0m     \x1b[1;33m^^^^\x1b[0m
+");
+}
+
+#[test]
+fn suggestions() {
+    assert_error_config!("opne file", "unexpected opne, expecting command or comment, did you mean `open`? on line 1, column 1");
+    assert_error_config!("nunamp o", "unexpected nunamp, expecting command or comment, did you mean `nunmap`? on line 1, column 1");
+    assert_error_config!("includ file.conf",
+        "unexpected includ, expecting command or comment, did you mean `include`? on line 1, column 1");
+    assert_error!("completely-unknown", "unexpected completely-unknown, expecting command or comment on line 1, column 1");
+    let result = parse_with_config("complete-nxt");
+    assert_eq!(result.errors[0].to_string(),
+        "unexpected complete-nxt, expecting command or comment, did you mean `complete-next`? on line 1, column 1");
+
+    let error = AppSettings::to_variant("zom", Int(1)).err().unwrap();
+    assert_eq!(error.to_string(), "no setting named zom, did you mean `zoom`?");
+    let error = AppSettings::to_variant("private-browsnig", Bool(true)).err().unwrap();
+    assert_eq!(error.to_string(), "no setting named private-browsnig, did you mean `private-browsing`?");
+    let error = AppSettings::to_variant("font", Int(1)).err().unwrap();
+    assert_eq!(error.to_string(), "no setting named font");
+    let error = NestedSettings::to_variant("hints.chrs", Str("a".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "no setting named hints.chrs, did you mean `hints.chars`?");
+    let error = NestedSettings::to_variant("hint.chars", Str("a".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "no setting named hint.chars, did you mean `hints.chars`?");
+
+    let error = AppSettings::to_variant("themes", List(vec![Str("lihgt".to_string())])).err().unwrap();
+    assert_eq!(error.to_string(),
+        "invalid value at index 0: unknown choice lihgt, expecting one of: dark, light, did you mean `light`?");
+    let error = ValidatedSettings::to_variant("align", Str("rigth".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "unknown choice rigth, expecting one of: left, right, did you mean `right`?");
+}
+
+#[test]
+fn json_output() {
+    let source = "set zoom = 1\nset zoom = {a = [1.5, \"x\\\"y\"], b = true}\nnmap <C-a>b open\nsett\n\
+        quit\nset title += \"\\u{1}\"";
+    let result = parse_with_config(source);
+    assert_eq!(json::to_json(&result, false), concat!(
+        r#"{"version":1,"diagnostics":["#,
+        r#"{"severity":"error","code":"unknown-command","#,
+        r#""message":"unexpected sett, expecting command or comment, did you mean `set`?","#,
+        r#""unexpected":"sett","expected":"command or comment","suggestion":"set","#,
+        r#""span":{"file":null,"start":{"line":4,"column":1},"end":{"line":4,"column":5},"bytes":{"start":71,"end":75}}},"#,
+        r#"{"severity":"warning","code":"duplicate-setting","message":"setting zoom is already set on line 1","#,
+        r#""unexpected":null,"expected":null,"suggestion":null,"#,
+        r#""span":{"file":null,"start":{"line":2,"column":5},"end":{"line":2,"column":9},"bytes":{"start":17,"end":21}}}"#,
+        r#"]}"#,
+    ));
+    let json = json::to_json(&result, true);
+    let commands = &json[json.find(r#""commands":"#).unwrap()..];
+    assert_eq!(commands, concat!(
+        r#""commands":["#,
+        r#"{"command":"set","name":"zoom","value":1,"#,
+        r#""span":{"file":null,"start":{"line":1,"column":1},"end":{"line":1,"column":13},"bytes":{"start":0,"end":12}}},"#,
+        r#"{"command":"set","name":"zoom","value":{"a":[1.5,"x\"y"],"b":true},"#,
+        r#""span":{"file":null,"start":{"line":2,"column":1},"end":{"line":2,"column":41},"bytes":{"start":13,"end":53}}},"#,
+        r#"{"command":"map","mode":"n","keys":"<C-a>b","action":"open","#,
+        r#""span":{"file":null,"start":{"line":3,"column":1},"end":{"line":3,"column":17},"bytes":{"start":54,"end":70}}},"#,
+        r#"{"command":"custom","name":"quit","argument":null,"count":null,"#,
+        r#""span":{"file":null,"start":{"line":5,"column":1},"end":{"line":5,"column":5},"bytes":{"start":76,"end":80}}},"#,
+        r#"{"command":"update","name":"title","operator":"+=","value":"\u0001","#,
+        r#""span":{"file":null,"start":{"line":6,"column":1},"end":{"line":6,"column":21},"bytes":{"start":81,"end":101}}}"#,
+        r#"]}"#,
+    ));
+
+    let mut parser = CommandParser::new();
+    let result = parser.parse_line("win-open \"crates.io\"", None);
+    assert!(json::to_json(&result, true).contains(r#"{"command":"custom","name":"win-open","argument":"\"crates.io\"","count":null,"#));
+    let result = parser.parse_line("scroll", Some(5));
+    assert!(json::to_json(&result, true).contains(r#"{"command":"custom","name":"scroll","argument":null,"count":5,"#));
+}
+
+#[test]
+fn setting_aliases() {
+    let mut settings = AliasedSettings::default();
+    settings.set_value(AliasedSettings::to_variant("zoom-level", Int(150)).unwrap());
+    assert_eq!(settings.zoom, 150);
+    settings.set_value(AliasedSettings::flag_variant("private").unwrap());
+    assert!(settings.private_browsing);
+    settings.set_value(AliasedSettings::flag_variant("noprivate").unwrap());
+    assert!(!settings.private_browsing);
+    settings.set_value(AliasedSettings::to_variant("hints.characters", Str("asdf".to_string())).unwrap());
+    assert_eq!(settings.hints.chars, "asdf");
+    assert_eq!(settings.get_value("zoom-level").unwrap(), Int(150));
+    assert_eq!(AliasedSettings::setting_names(), vec!["private-browsing", "zoom", "hints.chars"]);
+
+    let aliases = AliasedSettings::aliases();
+    assert_eq!(aliases.len(), 3);
+    assert_eq!(aliases[0].alias, "private");
+    assert_eq!(aliases[0].name, "private-browsing");
+    assert!(!aliases[0].deprecated);
+    assert_eq!(aliases[1].alias, "zoom-level");
+    assert_eq!(aliases[1].note, Some("the level is now a percentage".to_string()));
+    assert!(aliases[1].deprecated);
+    assert_eq!(aliases[2].alias, "hints.characters");
+    assert_eq!(aliases[2].name, "hints.chars");
+
+    let mut parser = CommandParser::new_with_config(Config {
+        setting_aliases: AliasedSettings::aliases(),
+        ..Config::default()
+    });
+    let result = parser.parse("set private\nset zoom-level = 150\nset nozoom-level\nunset hints.characters\n".as_bytes(),
+        None);
+    assert_eq!(result.commands, vec![
+        Flag("private-browsing".to_string()),
+        Set("zoom".to_string(), Int(150)),
+        Flag("nozoom".to_string()),
+        Reset("hints.chars".to_string()),
+    ]);
+    let warnings: Vec<_> = result.warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(warnings, vec![
+        "setting zoom-level is deprecated, use zoom instead: the level is now a percentage on line 2, column 5",
+        "setting zoom-level is deprecated, use zoom instead: the level is now a percentage on line 3, column 5",
+        "setting hints.characters is deprecated, use hints.chars instead on line 4, column 7",
+    ]);
+}
+
+#[test]
+fn number_literals() {
+    assert_setting!("offset", "-5", Set("offset".to_string(), Int(-5)));
+    assert_setting!("offset", "+5", Set("offset".to_string(), Int(5)));
+    assert_setting!("offset", "-9223372036854775808", Set("offset".to_string(), Int(i64::MIN)));
+    assert_setting!("offset", "0xff", Set("offset".to_string(), Int(255)));
+    assert_setting!("offset", "-0x7FFF_FFFF_FFFF_FFFF", Set("offset".to_string(), Int(-i64::MAX)));
+    assert_setting!("offset", "0o17", Set("offset".to_string(), Int(15)));
+    assert_setting!("offset", "0b1010_1010", Set("offset".to_string(), Int(170)));
+    assert_setting!("offset", "1_000_000", Set("offset".to_string(), Int(1_000_000)));
+    assert_setting!("offset", "-2.5", Set("offset".to_string(), Float(-2.5)));
+    assert_setting!("offset", "1e-3", Set("offset".to_string(), Float(1e-3)));
+    assert_setting!("offset", "1.5E+3", Set("offset".to_string(), Float(1500.0)));
+    assert_setting!("offset", "1e3", Set("offset".to_string(), Float(1000.0)));
+    assert_setting!("offset", "1_000.000_1", Set("offset".to_string(), Float(1000.0001)));
+    assert_setting!("offset", "[-1, 0x10]", Set("offset".to_string(), List(vec![Int(-1), Int(16)])));
+    assert_setting!("offset", "-", Set("offset".to_string(), Str("-".to_string())));
+    assert_setting!("offset", "-a", Set("offset".to_string(), Str("-a".to_string())));
+
+    assert_error!("set offset = 0x", "unexpected 0x, expecting number on line 1, column 14");
+    assert_error!("set offset = 0b102", "unexpected 0b102, expecting number on line 1, column 14");
+    assert_error!("set offset = 0x1_0000_0000_0000_0000",
+        "unexpected 0x1_0000_0000_0000_0000, expecting integer between -9223372036854775808 and 9223372036854775807 on line 1, column 14");
+    assert_error!("set offset = 1__", "unexpected 1__, expecting number on line 1, column 14");
+    assert_error!("set offset = 1_.5", "unexpected 1_.5, expecting number on line 1, column 14");
+    assert_error!("set offset = 1.", "unexpected 1., expecting number on line 1, column 14");
+    assert_error!("set offset = 1e", "unexpected 1e, expecting number on line 1, column 14");
+    assert_error!("set offset = 1e+-3", "unexpected 1e+-3, expecting number on line 1, column 14");
+    assert_setting!("offset", "-1-", Set("offset".to_string(), Str("-1-".to_string())));
+}
+
+#[test]
+fn float_setting() {
+    let mut settings = AppSettings::default();
+    settings.set_value(AppSettings::to_variant("scale", Float(1.5)).unwrap());
+    assert_eq!(settings.scale, 1.5);
+    settings.set_value(AppSettings::to_variant("scale", Int(2)).unwrap());
+    assert_eq!(settings.scale, 2.0);
+    let error = AppSettings::to_variant("scale", Str("2".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting float, but found string");
+}
+
+#[test]
+fn set_operators() {
+    assert_eq!(parse_string("set zoom += 10"), vec![Update("zoom".to_string(), SetOperator::Add, Int(10))]);
+    assert_eq!(parse_string("set zoom-=-10"), vec![Update("zoom".to_string(), SetOperator::Subtract, Int(-10))]);
+    assert_eq!(parse_string("set plugins -= flash"),
+        vec![Update("plugins".to_string(), SetOperator::Subtract, Str("flash".to_string()))]);
+    assert_eq!(parse_string("set list ^= [a, b] # Comment."),
+        vec![Update("list".to_string(), SetOperator::Prepend, List(vec![Str("a".to_string()), Str("b".to_string())]))]);
+    assert_eq!(parse_string("set option!"), vec![Toggle("option".to_string())]);
+    assert_eq!(parse_string("set option& # Comment."), vec![Reset("option".to_string())]);
+    assert_eq!(parse_string("  set option?  "), vec![Query("option".to_string())]);
+    assert_eq!(parse_string("set option"), vec![Flag("option".to_string())]);
+    assert_eq!(parse_string("set nooption # Comment."), vec![Flag("nooption".to_string())]);
+    assert_eq!(parse_string("set title = C++"), vec![Set("title".to_string(), Str("C++".to_string()))]);
+    assert_error!("set zoom +=", "unexpected <end of line>, expecting value on line 1, column 12");
+}
+
+#[test]
+fn relative_settings() {
+    let mut settings = AppSettings { zoom: 100, ..AppSettings::default() };
+    settings.set_value(settings.update_variant("zoom", SetOperator::Add, Int(10)).unwrap());
+    assert_eq!(settings.zoom, 110);
+    settings.set_value(settings.update_variant("zoom", SetOperator::Subtract, Int(20)).unwrap());
+    assert_eq!(settings.zoom, 90);
+    settings.set_value(settings.update_variant("zoom", SetOperator::Prepend, Int(2)).unwrap());
+    assert_eq!(settings.zoom, 180);
+    settings.scale = 1.0;
+    settings.set_value(settings.update_variant("scale", SetOperator::Add, Float(0.5)).unwrap());
+    assert_eq!(settings.scale, 1.5);
+
+    settings.title = "mg".to_string();
+    settings.set_value(settings.update_variant("title", SetOperator::Add, Str("-settings".to_string())).unwrap());
+    settings.set_value(settings.update_variant("title", SetOperator::Prepend, Str("the ".to_string())).unwrap());
+    assert_eq!(settings.title, "the mg-settings");
+    settings.set_value(settings.update_variant("title", SetOperator::Subtract, Str("the ".to_string())).unwrap());
+    assert_eq!(settings.title, "mg-settings");
+
+    settings.search_engines = vec!["duckduckgo".to_string()];
+    settings.set_value(settings.update_variant("search-engines", SetOperator::Add, Str("docs".to_string())).unwrap());
+    let value = List(vec![Str("crates".to_string()), Str("github".to_string())]);
+    settings.set_value(settings.update_variant("search-engines", SetOperator::Prepend, value).unwrap());
+    assert_eq!(settings.search_engines, vec!["crates", "github", "duckduckgo", "docs"]);
+    let value = List(vec![Str("github".to_string()), Str("docs".to_string())]);
+    settings.set_value(settings.update_variant("search-engines", SetOperator::Subtract, value).unwrap());
+    assert_eq!(settings.search_engines, vec!["crates", "duckduckgo"]);
+
+    let mut map = BTreeMap::new();
+    map.insert("docs.rs".to_string(), Int(150));
+    map.insert("crates.io".to_string(), Int(50));
+    settings.set_value(settings.update_variant("zoom-levels", SetOperator::Add, Value::Map(map)).unwrap());
+    settings.set_value(settings.update_variant("zoom-levels", SetOperator::Subtract, Str("crates.io".to_string())).unwrap());
+    assert_eq!(settings.zoom_levels.len(), 1);
+    assert_eq!(settings.zoom_levels["docs.rs"], 150);
+
+    let error = settings.update_variant("private-browsing", SetOperator::Add, Bool(true)).err().unwrap();
+    assert_eq!(error.to_string(), "operator += is not supported for a setting of type bool");
+    let error = settings.update_variant("zoom", SetOperator::Add, Str("a".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting integer, but found string");
+    let error = settings.update_variant("zoom-levels", SetOperator::Subtract, Int(1)).err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting string, but found int");
+    let error = settings.update_variant("unknown", SetOperator::Add, Int(1)).err().unwrap();
+    assert_eq!(error.to_string(), "no setting named unknown");
+}
+
+#[test]
+fn toggle_flag_and_reset_settings() {
+    let mut settings = AppSettings::default();
+    settings.set_value(settings.toggle_variant("private-browsing").unwrap());
+    assert!(settings.private_browsing);
+    settings.set_value(settings.toggle_variant("private-browsing").unwrap());
+    assert!(!settings.private_browsing);
+    settings.set_value(AppSettings::flag_variant("private-browsing").unwrap());
+    assert!(settings.private_browsing);
+    settings.set_value(AppSettings::flag_variant("noprivate-browsing").unwrap());
+    assert!(!settings.private_browsing);
+
+    let error = settings.toggle_variant("zoom").err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting bool, but found integer");
+    let error = AppSettings::flag_variant("nozoom").err().unwrap();
+    assert_eq!(error.to_string(), "no setting named nozoom, did you mean `zoom`?");
+    let error = AppSettings::flag_variant("zoom").err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting bool, but found integer");
+
+    settings.zoom = 150;
+    settings.set_value(AppSettings::default_variant("zoom").unwrap());
+    assert_eq!(settings.zoom, 0);
+}
+
+#[test]
+fn default_settings() {
+    let mut settings = DefaultSettings::default();
+    assert_eq!(settings, DefaultSettings {
+        hints: true,
+        scale: 1.0,
+        search_engines: vec!["duckduckgo".to_string()],
+        theme: Theme::Light,
+        title: "mg".to_string(),
+        zoom: 0,
+    });
+
+    settings.set_value(DefaultSettings::to_variant("title", Str("other".to_string())).unwrap());
+    settings.set_value(DefaultSettings::to_variant("zoom", Int(150)).unwrap());
+    settings.reset("title").unwrap();
+    assert_eq!(settings.title, "mg");
+    assert_eq!(settings.zoom, 150);
+    assert_eq!(settings.reset("titl").err().unwrap().to_string(), "no setting named titl, did you mean `title`?");
+
+    settings.set_value(DefaultSettings::to_variant("hints", Bool(false)).unwrap());
+    settings.reset_all();
+    assert_eq!(settings, DefaultSettings::default());
+}
+
+#[test]
+fn typed_settings() {
+    let mut settings = TypedSettings::default();
+    settings.set_value(TypedSettings::to_variant("port", Int(8080)).unwrap());
+    assert_eq!(settings.port, 8080);
+    settings.set_value(TypedSettings::to_variant("scroll", Int(-128)).unwrap());
+    assert_eq!(settings.scroll, -128);
+    settings.set_value(TypedSettings::to_variant("opacity", Float(0.5)).unwrap());
+    assert_eq!(settings.opacity, 0.5);
+    settings.set_value(TypedSettings::to_variant("opacity", Int(1)).unwrap());
+    assert_eq!(settings.opacity, 1.0);
+    settings.set_value(TypedSettings::to_variant("hint-char", Str("é".to_string())).unwrap());
+    assert_eq!(settings.hint_char, 'é');
+    settings.set_value(TypedSettings::to_variant("timeouts", List(vec![Int(10), Int(20)])).unwrap());
+    assert_eq!(settings.timeouts, vec![10, 20]);
+    settings.set_value(settings.update_variant("port", SetOperator::Add, Int(1)).unwrap());
+    assert_eq!(settings.port, 8081);
+    let error = settings.update_variant("port", SetOperator::Subtract, Int(9000)).err().unwrap();
+    assert_eq!(error.to_string(), "value 8081 - 9000 is out of range, expecting a value between 0 and 65535");
+    let error = settings.update_variant("port", SetOperator::Prepend, Int(10)).err().unwrap();
+    assert_eq!(error.to_string(), "value 8081 * 10 is out of range, expecting a value between 0 and 65535");
+
+    let error = TypedSettings::to_variant("port", Int(65536)).err().unwrap();
+    assert_eq!(error.to_string(), "value 65536 is out of range, expecting a value between 0 and 65535");
+    let error = TypedSettings::to_variant("timeouts", List(vec![Int(-1)])).err().unwrap();
+    assert_eq!(error.to_string(), "invalid value at index 0: value -1 is out of range, expecting a value between 0 and 4294967295");
+    let error = TypedSettings::to_variant("opacity", Float(1e300)).err().unwrap();
+    assert_eq!(error.to_string(), "value 1e300 is out of range, expecting a value between -3.4028235e38 and 3.4028235e38");
+    let error = TypedSettings::to_variant("hint-char", Str("ab".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting character, but found string");
+    let error = TypedSettings::to_variant("port", Str("80".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting integer, but found string");
+
+    settings.set_value(TypedSettings::to_variant("proxy", Str("localhost".to_string())).unwrap());
+    assert_eq!(settings.proxy, Some("localhost".to_string()));
+    assert_eq!(settings.get_value("proxy").unwrap(), Str("localhost".to_string()));
+    settings.set_value(TypedSettings::to_variant("proxy", Str("none".to_string())).unwrap());
+    assert_eq!(settings.proxy, None);
+    assert_eq!(settings.get_value("proxy").unwrap(), Str("none".to_string()));
+    settings.set_value(TypedSettings::to_variant("tab-limit", Int(10)).unwrap());
+    assert_eq!(settings.tab_limit, Some(10));
+    let error = TypedSettings::to_variant("tab-limit", Bool(true)).err().unwrap();
+    assert_eq!(error.to_string(), "wrong value type: expecting integer, but found bool");
+    assert_eq!(TypedSettings::setting_type("tab-limit").unwrap().to_string(), "integer or none");
+    assert_eq!(TypedSettings::setting_type("download-dir").unwrap(), ValueType::Path);
+
+    let home = env::var("HOME").unwrap();
+    settings.set_value(TypedSettings::to_variant("download-dir", Str("~/downloads".to_string())).unwrap());
+    assert_eq!(settings.download_dir, PathBuf::from(&home).join("downloads"));
+    assert_eq!(settings.config_dir, PathBuf::from(&home).join(".config"));
+    settings.set_value(TypedSettings::to_variant("download-dir", Str("/tmp/~".to_string())).unwrap());
+    assert_eq!(settings.download_dir, PathBuf::from("/tmp/~"));
+    assert_eq!(settings.get_value("download-dir").unwrap(), Str("/tmp/~".to_string()));
+
+    let completions = TypedSettings::get_value_completions();
+    assert_eq!(completions["proxy"], vec!["none".to_string()]);
+    assert!(completions["port"].is_empty());
+}
+
+#[test]
+fn validated_settings() {
+    let mut settings = ValidatedSettings::default();
+    settings.set_value(ValidatedSettings::to_variant("zoom", Int(25)).unwrap());
+    settings.set_value(ValidatedSettings::to_variant("zoom", Int(500)).unwrap());
+    assert_eq!(settings.zoom, 500);
+    let error = ValidatedSettings::to_variant("zoom", Int(501)).err().unwrap();
+    assert_eq!(error.to_string(), "value 501 is out of range, expecting a value between 25 and 500");
+    let error = ValidatedSettings::to_variant("zoom", Int(24)).err().unwrap();
+    assert_eq!(error.to_string(), "value 24 is out of range, expecting a value between 25 and 500");
+    let error = settings.update_variant("zoom", SetOperator::Add, Int(1)).err().unwrap();
+    assert_eq!(error.to_string(), "value 501 is out of range, expecting a value between 25 and 500");
+
+    settings.set_value(ValidatedSettings::to_variant("opacity", Float(0.5)).unwrap());
+    assert_eq!(settings.opacity, 0.5);
+    let error = ValidatedSettings::to_variant("opacity", Float(1.5)).err().unwrap();
+    assert_eq!(error.to_string(), "value 1.5 is out of range, expecting a value between 0 and 1");
+
+    settings.set_value(ValidatedSettings::to_variant("scroll", Str("none".to_string())).unwrap());
+    settings.set_value(ValidatedSettings::to_variant("scroll", Int(-10)).unwrap());
+    assert_eq!(settings.scroll, Some(-10));
+    let error = ValidatedSettings::to_variant("scroll", Int(-11)).err().unwrap();
+    assert_eq!(error.to_string(), "value -11 is out of range, expecting a value greater than or equal to -10");
+
+    settings.set_value(ValidatedSettings::to_variant("align", Str("left".to_string())).unwrap());
+    assert_eq!(settings.align, "left");
+    let error = ValidatedSettings::to_variant("align", Str("center".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "unknown choice center, expecting one of: left, right");
+    assert_eq!(ValidatedSettings::get_value_completions()["align"], vec!["left", "right"]);
+
+    let error = ValidatedSettings::to_variant("search-engines", List(vec![])).err().unwrap();
+    assert_eq!(error.to_string(), "invalid value: the value cannot be empty");
+    settings.set_value(ValidatedSettings::to_variant("search-engines", List(vec![Str("ddg".to_string())])).unwrap());
+    let error = settings.update_variant("search-engines", SetOperator::Subtract, Str("ddg".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "invalid value: the value cannot be empty");
+
+    settings.set_value(ValidatedSettings::to_variant("url", Str("https://crates.io".to_string())).unwrap());
+    let error = ValidatedSettings::to_variant("url", Str("http://crates.io".to_string())).err().unwrap();
+    assert_eq!(error.to_string(), "invalid value: http://crates.io is not an https URL");
+    let error = ValidatedSettings::to_variant("url", Str(String::new())).err().unwrap();
+    assert_eq!(error.to_string(), "invalid value:  is not an https URL");
+}
+
+#[test]
+fn unset_command() {
+    assert_eq!(parse_string("unset option # Comment."), vec![Reset("option".to_string())]);
+    assert_error!("unset", "unexpected <end of line>, expecting command arguments on line 1, column 6");
+    assert_error!("unset option value", "unexpected value, expecting <end of line> on line 1, column 14");
+    assert_error!("unset 5", "unexpected 5, expecting identifier on line 1, column 7");
+}
+
+#[test]
+fn settings_introspection() {
+    let mut settings = AppSettings {
+        private_browsing: true,
+        scale: 1.5,
+        themes: vec![Theme::Light],
+        title: "mg".to_string(),
+        zoom: 150,
+        ..AppSettings::default()
+    };
+    settings.zoom_levels.insert("docs.rs".to_string(), 120);
+    assert_eq!(settings.get_value("private-browsing").unwrap(), Bool(true));
+    assert_eq!(settings.get_value("scale").unwrap(), Float(1.5));
+    assert_eq!(settings.get_value("themes").unwrap(), List(vec![Str("light".to_string())]));
+    assert_eq!(settings.get_value("title").unwrap(), Str("mg".to_string()));
+    assert_eq!(settings.get_value("zoom").unwrap(), Int(150));
+    let mut map = BTreeMap::new();
+    map.insert("docs.rs".to_string(), Int(120));
+    assert_eq!(settings.get_value("zoom-levels").unwrap(), Value::Map(map));
+    assert_eq!(settings.get_value("zoom2").err().unwrap().to_string(), "no setting named zoom2, did you mean `zoom`?");
+
+    for name in AppSettings::setting_names() {
+        let value = settings.get_value(&name).unwrap();
+        settings.set_value(AppSettings::to_variant(&name, value).unwrap());
+    }
+    assert_eq!(settings.zoom_levels["docs.rs"], 120);
+    assert_eq!(AppSettings::setting_names(), vec!["headers", "private-browsing", "search-engines", "scale", "sizes",
+        "themes", "title", "zoom", "zoom-levels"]);
+
+    assert_eq!(AppSettings::setting_type("private-browsing").unwrap(), ValueType::Bool);
+    assert_eq!(AppSettings::setting_type("scale").unwrap(), ValueType::Float);
+    assert_eq!(AppSettings::setting_type("sizes").unwrap(), ValueType::List(Box::new(ValueType::Int)));
+    let theme_type = ValueType::Choice(vec!["dark".to_string(), "light".to_string()]);
+    assert_eq!(AppSettings::setting_type("themes").unwrap(), ValueType::List(Box::new(theme_type)));
+    assert_eq!(AppSettings::setting_type("title").unwrap(), ValueType::Str);
+    assert_eq!(AppSettings::setting_type("headers").unwrap().to_string(), "map of string");
+    assert_eq!(AppSettings::setting_type("themes").unwrap().to_string(), "list of one of: dark, light");
+    assert!(AppSettings::setting_type("zoom2").is_err());
+}
+
+#[test]
+fn quoted_string() {
+    assert_setting!("title", "\"42\"", Set("title".to_string(), Str("42".to_string())));
+    assert_setting!("title", "'true'", Set("title".to_string(), Str("true".to_string())));
+    assert_setting!("title", "\"  # not a comment  \" # Comment.",
+        Set("title".to_string(), Str("  # not a comment  ".to_string())));
+    assert_setting!("title", r#""a\tb\nc\"d\\e\'f""#, Set("title".to_string(), Str("a\tb\nc\"d\\e'f".to_string())));
+    assert_setting!("title", r#"'it\'s "quoted"'"#, Set("title".to_string(), Str("it's \"quoted\"".to_string())));
+    assert_setting!("title", r#""caf\u{e9} \u{1F600}""#, Set("title".to_string(), Str("café 😀".to_string())));
+    assert_setting!("title", "[\"a, b\", 'c]']", Set("title".to_string(),
+        List(vec![Str("a, b".to_string()), Str("c]".to_string())])));
+    assert_error!("set title = \"unterminated", "unexpected <end of line>, expecting \" on line 1, column 26");
+    assert_error!("set title = 'unterminated\\'", "unexpected <end of line>, expecting ' on line 1, column 28");
+    assert_error!("set title = \"a\\qb\"", "unexpected \\q, expecting escape sequence on line 1, column 15");
+    assert_error!("set title = \"\\u{110000}\"",
+        "unexpected \\u{110000, expecting unicode escape sequence like \\u{00e9} on line 1, column 14");
+    assert_error!("set title = \"\\u00e9\"",
+        "unexpected \\u00e9\", expecting unicode escape sequence like \\u{00e9} on line 1, column 14");
+    assert_error!("set title = \"a\" b", "unexpected b, expecting <end of line> on line 1, column 17");
 }
 
 #[test]
@@ -297,6 +1323,22 @@ fn set_command() {
     assert_eq!(parse_string("set option1 = 42\nset option2 = 12.345\n"), vec![Set("option1".to_string(), Int(42)), Set("option2".to_string(), Float(12.345))]);
     assert_eq!(parse_string("set option1 = 42\n\nset option2 = 12.345\n"), vec![Set("option1".to_string(), Int(42)), Set("option2".to_string(), Float(12.345))]);
     assert_eq!(parse_string("  set    option1    =    42    "), vec![Set("option1".to_string(), Int(42))]);
+    assert_eq!(parse_string("set option1=42"), vec![Set("option1".to_string(), Int(42))]);
+    assert_eq!(parse_string("set option1= 42"), vec![Set("option1".to_string(), Int(42))]);
+    assert_eq!(parse_string("set option1 =42"), vec![Set("option1".to_string(), Int(42))]);
+    assert_eq!(parse_string("set option1 = http://a.b/?c=d#e # Comment."),
+        vec![Set("option1".to_string(), Str("http://a.b/?c=d#e".to_string()))]);
+}
+
+#[test]
+fn non_ascii_columns() {
+    assert_error!("set été 5", "unexpected 5, expecting = on line 1, column 9");
+    assert_error!("set été = [1 2]", "unexpected 2, expecting , or ] on line 1, column 14");
+    assert_error!("set été = \"à", "unexpected <end of line>, expecting \" on line 1, column 13");
+    assert_error!("  é", "unexpected é, expecting command or comment on line 1, column 3");
+    assert_error!("ééé", "unexpected ééé, expecting command or comment on line 1, column 1");
+    assert_error!("éééunmap", "unexpected éééunmap, expecting command or comment on line 1, column 1");
+    assert_error_config!("nunmap <F1> é", "unexpected é, expecting <end of line> on line 1, column 13");
 }
 
 #[test]
@@ -314,6 +1356,7 @@ fn parse_error_with_config(input: &str) -> Vec<Error> {
     let mut parser = CommandParser::new_with_config(Config {
         application_commands: vec![],
         mapping_modes: vec!["n", "i", "c"],
+        ..Config::default()
     });
     parser.set_include_path("tests");
     parser.parse(input.as_bytes(), None).errors
@@ -334,6 +1377,7 @@ fn parse_line_with_config(input: &str) -> ParseResult<CustomCommand> {
     let mut parser = CommandParser::new_with_config(Config {
         application_commands: vec!["complete-next"],
         mapping_modes: vec!["n", "i", "c"],
+        ..Config::default()
     });
     parser.parse_line(input, None)
 }
@@ -342,6 +1386,7 @@ fn parse_with_config(input: &str) -> ParseResult<CustomCommand> {
     let mut parser = CommandParser::new_with_config(Config {
         application_commands: vec!["complete-next"],
         mapping_modes: vec!["n", "i", "c"],
+        ..Config::default()
     });
     parser.parse(input.as_bytes(), None)
 }